@@ -1,11 +1,11 @@
 use adv_code_2025::*;
 use anyhow::*;
 use code_timing_macros::time_snippet;
-use const_format::{concatcp, str_get};
-use itertools::Itertools;
-use std::fs::{read, File};
-use std::io::{BufRead, BufReader, Cursor};
-use std::ops::{Div, Rem};
+use const_format::concatcp;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::str::FromStr;
 
 const DAY: &str = "01";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
@@ -27,83 +27,24 @@ fn main() -> Result<()> {
     start_day(DAY);
 
     //region Part 1
-    // println!("=== Part 1 ===");
-    //
-    // fn part1<R: BufRead>(reader: R) -> Result<usize> {
-    //     let start = 50;
-    //     let mut point = start;
-    //     let mut pass = 0;
-    //
-    //     for line in reader.lines() {
-    //         let line = line?;
-    //         let line = line.trim();
-    //         if line.is_empty() {
-    //             continue;
-    //         }
-    //         let (a, b) = line.split_at(1);
-    //         let n: i32 = b.parse()?;
-    //         point = match a {
-    //             "L" => point - n,
-    //             "R" => point + n,
-    //             _ => panic!("Invalid rotation"),
-    //         } % 100;
-    //         if point == 0 {
-    //             pass += 1;
-    //         } else if point < 0 {
-    //             point += 100;
-    //         }
-    //     }
-    //
-    //     Ok(pass)
-    // }
-    //
-    // assert_eq!(3, part1(BufReader::new(TEST.as_bytes()))?);
-    //
-    // let input_file = BufReader::new(File::open(INPUT_FILE)?);
-    // let result = time_snippet!(part1(input_file)?);
-    // println!("Result = {}", result);
+    println!("=== Part 1 ===");
+
+    fn part1<R: BufRead>(reader: R) -> Result<usize> {
+        Ok(read_password(reader, Dial::new(100, 50)?)?.landings as usize)
+    }
+
+    assert_eq!(3, part1(BufReader::new(TEST.as_bytes()))?);
+
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let result = time_snippet!(part1(input_file)?);
+    println!("Result = {}", result);
     //endregion
 
     //region Part 2
     println!("\n=== Part 2 ===");
 
     fn part2<R: BufRead>(reader: R) -> Result<usize> {
-        let start = 50;
-        let mut point = start;
-        let mut pass = 0;
-
-        for line in reader.lines() {
-            let line = line?;
-            let line = line.trim();
-            if line.is_empty() {
-                continue;
-            }
-            let (a, b) = line.split_at(1);
-            let n = b.parse::<i32>()?;
-            let mut is_left = false;
-            let pre = point;
-            point = match a {
-                "L" => {
-                    is_left = true;
-                    point - n
-                }
-                "R" => point + n,
-                _ => return Err(anyhow!("Invalid rotation")),
-            };
-            // core: edge case, left rotation and not start from 0
-            if is_left && point <= 0 && pre != 0 {
-                pass += 1;
-            }
-
-            // this is only calc the 1 circle pass
-            let t = point.div(100).unsigned_abs();
-            pass += t as usize;
-
-            point = point.rem_euclid(100);
-
-            // println!("{point} {pass}");
-        }
-        Ok(pass)
+        Ok(read_password(reader, Dial::new(100, 50)?)?.zero_hits as usize)
     }
 
     assert_eq!(6, part2(BufReader::new(TEST.as_bytes()))?);
@@ -114,11 +55,271 @@ fn main() -> Result<()> {
     println!("Result = {}", result);
     //endregion
 
+    //region Trace
+    println!("\n=== Trace ===");
+
+    let mut dial = Dial::new(100, 50)?;
+    for turn in dial.trace(read_rotations(BufReader::new(TEST.as_bytes()))?) {
+        println!("{turn}");
+    }
+    //endregion
+
+    //region Start positions
+    println!("\n=== Start positions ===");
+
+    let analysis = analyze_starts(&read_rotations(BufReader::new(TEST.as_bytes()))?, 100)?;
+    assert_eq!(6, analysis.hits[50]);
+
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let analysis = time_snippet!(analyze_starts(&read_rotations(input_file)?, 100)?);
+    let (best, worst) = (analysis.best(), analysis.worst());
+    println!("Best start = {} ({} hits)", best.0, best.1);
+    println!("Worst start = {} ({} hits)", worst.0, worst.1);
+    //endregion
+
     Ok(())
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rotation {
+    Left(u64),
+    Right(u64),
+}
+
+impl FromStr for Rotation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (a, b) = s.split_at_checked(1).ok_or(anyhow!("Empty rotation"))?;
+        let n = b.parse::<u64>()?;
+        match a {
+            "L" => Ok(Rotation::Left(n)),
+            "R" => Ok(Rotation::Right(n)),
+            _ => Err(anyhow!("Invalid rotation: {s}")),
+        }
+    }
+}
+
+/// What one rotation did to the dial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Turn {
+    rotation: Rotation,
+    start: u64,
+    end: u64,
+    /// The rotation finished pointing at zero.
+    landed: bool,
+    /// Every click that pointed at zero, the Part 2 count.
+    zero_hits: u64,
+}
+
+impl Turn {
+    /// Zero hits before the last click, i.e. passes straight through zero. A zero-length
+    /// rotation can land without a click, so it has nothing to subtract.
+    fn crossings(&self) -> u64 {
+        self.zero_hits.saturating_sub(self.landed as u64)
+    }
+}
+
+impl fmt::Display for Rotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rotation::Left(n) => write!(f, "L{n}"),
+            Rotation::Right(n) => write!(f, "R{n}"),
+        }
+    }
+}
+
+impl fmt::Display for Turn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>6}: {:>2} -> {:>2}, {} zero hits ({} passing, {})",
+            self.rotation.to_string(),
+            self.start,
+            self.end,
+            self.zero_hits,
+            self.crossings(),
+            if self.landed { "landed" } else { "not landed" }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Dial {
+    size: u64,
+    position: u64,
+}
+
+impl Dial {
+    fn new(size: u64, start: u64) -> Result<Self> {
+        ensure!(size > 0, "Dial needs at least one position");
+        ensure!(start < size, "Start {start} is off a dial of size {size}");
+        Ok(Self {
+            size,
+            position: start,
+        })
+    }
+
+    fn rotate(&mut self, rotation: Rotation) -> Turn {
+        let start = self.position;
+        let (full_turns, rest) = match rotation {
+            Rotation::Left(n) | Rotation::Right(n) => (n / self.size, n % self.size),
+        };
+
+        // Each full turn passes zero exactly once; the remainder reaches it at most once more,
+        // and never when leaving from zero itself. Comparing `rest` against the distance to zero
+        // keeps every intermediate below `size`, so huge dials and rotations can't overflow.
+        let (end, reaches_zero) = match rotation {
+            Rotation::Left(_) if rest <= start => (start - rest, start != 0 && rest == start),
+            Rotation::Left(_) => (self.size - (rest - start), start != 0),
+            Rotation::Right(_) if rest >= self.size - start => {
+                (rest - (self.size - start), start != 0)
+            }
+            Rotation::Right(_) => (start + rest, false),
+        };
+        self.position = end;
+        Turn {
+            rotation,
+            start,
+            end,
+            landed: end == 0,
+            zero_hits: full_turns + reaches_zero as u64,
+        }
+    }
+
+    fn trace<'a, I>(&'a mut self, rotations: I) -> impl Iterator<Item = Turn> + 'a
+    where
+        I: IntoIterator<Item = Rotation>,
+        I::IntoIter: 'a,
+    {
+        rotations.into_iter().map(|r| self.rotate(r))
+    }
+}
+
+/// Both password protocols, counted in one pass over the rotations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Password {
+    /// Rotations that stopped on zero (Part 1).
+    landings: u64,
+    /// Clicks that pointed at zero, including passes (Part 2).
+    zero_hits: u64,
+}
+
+fn read_password<R: BufRead>(mut reader: R, mut dial: Dial) -> Result<Password> {
+    let mut password = Password::default();
+    let mut line = String::new();
+
+    while reader.read_line(&mut line)? != 0 {
+        let rotation = line.trim();
+        if !rotation.is_empty() {
+            let turn = dial.rotate(rotation.parse()?);
+            password.landings += turn.landed as u64;
+            password.zero_hits = password
+                .zero_hits
+                .checked_add(turn.zero_hits)
+                .ok_or(anyhow!("Zero count overflowed at {rotation}"))?;
+        }
+        line.clear();
+    }
+
+    Ok(password)
+}
+
+fn read_rotations<R: BufRead>(reader: R) -> Result<Vec<Rotation>> {
+    let mut rotations = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() {
+            rotations.push(line.parse()?);
+        }
+    }
+    Ok(rotations)
+}
+
+/// Part 2 zero hits for every start position, indexed by start.
+#[derive(Debug, Clone)]
+struct StartAnalysis {
+    hits: Vec<u64>,
+}
+
+impl StartAnalysis {
+    /// The start with the most zero hits, preferring the lowest position on ties.
+    fn best(&self) -> (usize, u64) {
+        let max = *self.hits.iter().max().unwrap();
+        (self.hits.iter().position(|&h| h == max).unwrap(), max)
+    }
+
+    /// The start with the fewest zero hits, preferring the lowest position on ties.
+    fn worst(&self) -> (usize, u64) {
+        let min = *self.hits.iter().min().unwrap();
+        (self.hits.iter().position(|&h| h == min).unwrap(), min)
+    }
+}
+
+/// Counts zero hits from every start of a dial with `size` positions without re-simulating.
+///
+/// Position after a rotation is always `start + offset (mod size)`, where the offset does not
+/// depend on the start. Like in `Dial::rotate`, each rotation contributes its full turns to every
+/// start, plus one more hit for the starts whose position lies within the remainder's reach of
+/// zero; that set is a cyclic interval of starts, added to a difference array in O(1).
+fn analyze_starts(rotations: &[Rotation], size: usize) -> Result<StartAnalysis> {
+    ensure!(size > 0, "Dial needs at least one position");
+    let m = size as u64;
+    let mut full_turns: u64 = 0;
+    let mut diff = vec![0i64; size + 1];
+    let mut offset: u64 = 0;
+
+    // Adds one to every start `s` whose position `s + offset` lies in `lo..=hi`.
+    let mut mark = |lo: u64, hi: u64, offset: u64| {
+        let lo = ((lo + m - offset) % m) as usize;
+        let hi = ((hi + m - offset) % m) as usize;
+        if lo <= hi {
+            diff[lo] += 1;
+            diff[hi + 1] -= 1;
+        } else {
+            diff[lo] += 1;
+            diff[size] -= 1;
+            diff[0] += 1;
+            diff[hi + 1] -= 1;
+        }
+    };
+
+    for &rotation in rotations {
+        let (Rotation::Left(n) | Rotation::Right(n)) = rotation;
+        let rest = n % m;
+        full_turns = full_turns
+            .checked_add(n / m)
+            .ok_or(anyhow!("Zero count overflowed"))?;
+        if rest == 0 {
+            continue;
+        }
+        match rotation {
+            // Leaving from 1..=rest to the left reaches zero.
+            Rotation::Left(_) => {
+                mark(1, rest, offset);
+                offset = (offset + m - rest) % m;
+            }
+            // Leaving from size-rest..size to the right reaches zero.
+            Rotation::Right(_) => {
+                mark(m - rest, m - 1, offset);
+                offset = (offset + rest) % m;
+            }
+        }
+    }
+
+    let mut hits = Vec::with_capacity(size);
+    let mut extra = 0i64;
+    for d in &diff[..size] {
+        extra += d;
+        hits.push(full_turns + extra as u64);
+    }
+    Ok(StartAnalysis { hits })
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
     use std::ops::Div;
 
     #[test]
@@ -128,8 +329,98 @@ mod tests {
         println!("{a} {b}")
     }
 
+    #[test]
+    fn test_trace_matches_clicks() {
+        let mut instructions: Vec<&str> = TEST.lines().filter(|l| !l.is_empty()).collect();
+        instructions.extend(["L50", "R100", "L100", "R250", "L349", "R1", "L0", "R99"]);
+        let rotations = instructions.iter().map(|l| l.parse().unwrap());
+
+        let mut dial = Dial::new(100, 50).unwrap();
+        for (turn, instruction) in dial.trace(rotations).zip(&instructions) {
+            let clicks = solve_safe_dial(turn.start as i32, vec![instruction]);
+            assert_eq!(clicks as u64, turn.zero_hits, "{turn:?}");
+        }
+    }
+
+    #[test]
+    fn test_small_dial() {
+        let mut dial = Dial::new(5, 0).unwrap();
+        let turn = dial.rotate(Rotation::Left(12));
+        assert_eq!(
+            (0, 3, false, 2),
+            (turn.start, turn.end, turn.landed, turn.crossings())
+        );
+        let turn = dial.rotate(Rotation::Right(2));
+        assert_eq!((0, true, 0), (turn.end, turn.landed, turn.crossings()));
+        let turn = dial.rotate(Rotation::Left(0));
+        assert_eq!(
+            (true, 0, 0),
+            (turn.landed, turn.zero_hits, turn.crossings())
+        );
+        assert!(Dial::new(5, 5).is_err());
+
+        let mut dial = Dial::new(100, 50).unwrap();
+        let turn = dial.rotate("L68".parse().unwrap());
+        assert_eq!(
+            "   L68: 50 -> 82, 1 zero hits (1 passing, not landed)",
+            turn.to_string()
+        );
+    }
+
+    #[test]
+    fn test_read_password() {
+        let dial = Dial::new(100, 50).unwrap();
+        let password = read_password(BufReader::new(TEST.as_bytes()), dial).unwrap();
+        assert_eq!(
+            Password {
+                landings: 3,
+                zero_hits: 6
+            },
+            password
+        );
+
+        // CRLF, and a rotation far beyond i32.
+        let input = "R999999999950\r\nL1\r\n";
+        let password = read_password(BufReader::new(input.as_bytes()), dial).unwrap();
+        assert_eq!(
+            Password {
+                landings: 1,
+                zero_hits: 10_000_000_000
+            },
+            password
+        );
+
+        let huge = Dial::new(u64::MAX, u64::MAX - 1).unwrap();
+        let input = format!("R{}\nR{}\n", u64::MAX, u64::MAX);
+        let password = read_password(BufReader::new(input.as_bytes()), huge).unwrap();
+        assert_eq!(2, password.zero_hits);
+        assert!(read_password(BufReader::new("X12\n".as_bytes()), dial).is_err());
+    }
+
+    #[test]
+    fn test_analyze_starts() {
+        let mut instructions: Vec<&str> = TEST.lines().filter(|l| !l.is_empty()).collect();
+        instructions.extend(["R250", "L349", "R1", "L0", "R99", "L7"]);
+        let rotations: Vec<Rotation> = instructions.iter().map(|l| l.parse().unwrap()).collect();
+
+        for size in [1, 7, 100] {
+            let analysis = analyze_starts(&rotations, size).unwrap();
+            for (start, &hits) in analysis.hits.iter().enumerate() {
+                let mut dial = Dial::new(size as u64, start as u64).unwrap();
+                let expected: u64 = dial
+                    .trace(rotations.iter().copied())
+                    .map(|t| t.zero_hits)
+                    .sum();
+                assert_eq!(expected, hits, "size {size} start {start}");
+            }
+        }
+
+        let analysis = analyze_starts(&rotations[..10], 100).unwrap();
+        assert_eq!(6, analysis.hits[50]);
+        assert!(analysis.best().1 >= 6 && analysis.worst().1 <= 6);
+    }
+
     // This brute-force generated by gemini to check the right answer
-    #[allow(dead_code)]
     fn solve_safe_dial(start_pos: i32, instructions: Vec<&str>) -> usize {
         let mut current_pos = start_pos;
         let mut zero_hits = 0;
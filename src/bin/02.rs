@@ -1,3 +1,5 @@
+use adv_code_2025::digits::Digits;
+use adv_code_2025::ranges::{RangeReader, RangeToken};
 use adv_code_2025::*;
 use anyhow::*;
 use code_timing_macros::time_snippet;
@@ -18,91 +20,24 @@ fn main() -> Result<()> {
     start_day(DAY);
 
     //region Part 1
-    // println!("=== Part 1 ===");
-    //
-    // fn part1<R: BufRead>(mut reader: R) -> Result<usize> {
-    //     let mut answer: Vec<usize> = Vec::new();
-    //     let mut buf = vec![];
-    //     loop {
-    //         let num = reader.read_until(b',', &mut buf)?;
-    //         if num == 0 {
-    //             break;
-    //         }
-    //         let s = String::from_utf8_lossy(&buf);
-    //         let (a, b) = s
-    //             .trim_start_matches('\n')
-    //             .trim_end_matches([',', '\n'])
-    //             .split_once('-')
-    //             .ok_or(anyhow!("Invalid input format"))?;
-    //         let start: usize = dbg!(a).parse()?;
-    //         let end: usize = dbg!(b).parse()?;
-    //
-    //         for num in start..=end {
-    //             if num < 10 {
-    //                 continue;
-    //             }
-    //             let digits = match num.checked_ilog10() {
-    //                 Some(exp) => exp + 1,
-    //                 None => continue,
-    //             };
-    //             if digits % 2 != 0 {
-    //                 continue;
-    //             }
-    //
-    //             let half = digits / 2;
-    //             let divisor: usize = 10usize.pow(half);
-    //             if (num / divisor) == (num % divisor) {
-    //                 answer.push(num);
-    //             }
-    //         }
-    //
-    //         buf.clear();
-    //     }
-    //
-    //     Ok(answer.iter().sum())
-    // }
-    //
-    // assert_eq!(1227775554, part1(BufReader::new(TEST.as_bytes()))?);
-    //
-    // let input_file = BufReader::new(File::open(INPUT_FILE)?);
-    // let result = time_snippet!(part1(input_file)?);
-    // println!("Result = {}", result);
+    println!("=== Part 1 ===");
+
+    fn part1<R: BufRead>(reader: R) -> Result<usize> {
+        sum_ranges(reader, 10, Repeats::Twice)
+    }
+
+    assert_eq!(1227775554, part1(BufReader::new(TEST.as_bytes()))?);
+
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let result = time_snippet!(part1(input_file)?);
+    println!("Result = {}", result);
     //endregion
 
     //region Part 2
     println!("\n=== Part 2 ===");
 
-    fn part2<R: BufRead>(mut reader: R) -> Result<usize> {
-        let mut answer = vec![];
-        let mut buf = vec![];
-        loop {
-            if reader.read_until(b',', &mut buf)? == 0 {
-                break;
-            }
-            let s = String::from_utf8_lossy(&buf);
-            let (a, b) = s
-                .trim_start_matches('\n')
-                .trim_end_matches([',', '\n'])
-                .split_once('-')
-                .ok_or(anyhow!("Invalid input format"))?;
-            let start: usize = a.parse()?;
-            let end: usize = b.parse()?;
-
-            for i in start..=end {
-                // let s = i.to_string();
-                // let bytes = s.as_bytes();
-                // if check(bytes) {
-                //     answer.push(i);
-                // }
-                if check_leetcode_459(i.to_string()) {
-                    answer.push(i);
-                }
-            }
-
-            buf.clear();
-        }
-        // println!("{:?}", answer);
-        Ok(answer.iter().sum())
+    fn part2<R: BufRead>(reader: R) -> Result<usize> {
+        sum_ranges(reader, 10, Repeats::AtLeastTwice)
     }
 
     assert_eq!(4174379265, part2(BufReader::new(TEST.as_bytes()))?);
@@ -113,16 +48,29 @@ fn main() -> Result<()> {
     println!("Result = {}", result);
     //endregion
 
+    //region Audit
+    println!("\n=== Audit ===");
+
+    let reports = audit(&read_ranges(BufReader::new(TEST.as_bytes()), 10)?, 10)?;
+    assert_eq!(1227775554, reports.iter().map(|r| r.part1).sum::<u128>());
+    assert_eq!(4174379265, reports.iter().map(|r| r.part2).sum::<u128>());
+    for report in &reports {
+        println!("{report}");
+    }
+    //endregion
+
     Ok(())
 }
 
 // see https://www.reddit.com/r/adventofcode/comments/1pbzqcx/2025_day_2_solutions/
+#[cfg(test)]
 #[inline(always)]
 fn check_leetcode_459(s: String) -> bool {
     let new_s = s.repeat(2);
     new_s[1..new_s.len() - 1].contains(&s)
 }
 
+#[cfg(test)]
 fn check(bytes: &[u8]) -> bool {
     let len = bytes.len();
     for d in 1..=(len / 2) {
@@ -149,12 +97,233 @@ fn check(bytes: &[u8]) -> bool {
     false
 }
 
-fn pow10(exp: u32) -> u128 {
-    10u128.pow(exp)
+/// Whether `id`, written in `radix`, is one block repeated at least twice.
+#[cfg(test)]
+fn is_repeated(id: u128, radix: u32) -> bool {
+    check(id.to_radix_string(radix).as_bytes())
+}
+
+fn read_ranges<R: BufRead>(reader: R, radix: u32) -> Result<Vec<(u128, u128)>> {
+    RangeReader::new(reader)
+        .radix(radix)
+        .map(|token| token.map(|t| (t.start, t.end)))
+        .collect()
+}
+
+fn sum_ranges<R: BufRead>(reader: R, radix: u32, repeats: Repeats) -> Result<usize> {
+    let mut answer: u128 = 0;
+    for token in RangeReader::new(reader).radix(radix) {
+        let RangeToken { start, end, .. } = token?;
+        answer += sum_invalid(start, end, radix, repeats)?;
+    }
+    Ok(answer.try_into()?)
+}
+
+/// Which repeated-block IDs count as invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repeats {
+    /// A seed written exactly twice, e.g. `123123` (Part 1).
+    Twice,
+    /// A seed written two or more times, e.g. `121212` (Part 2).
+    AtLeastTwice,
+}
+
+/// Splits `start..=end` into `(len, lo, hi)` pieces whose numbers all have `len` digits in `radix`.
+fn by_length(start: u128, end: u128, radix: u32) -> Result<Vec<(u32, u128, u128)>> {
+    ensure!((2..=36).contains(&radix), "Radix {radix} out of range");
+    ensure!(start <= end, "Reversed range {start}-{end}");
+    let lengths = start.digit_count_radix(radix)..=end.digit_count_radix(radix);
+    Ok(lengths
+        .map(|len| {
+            let lo = start.max(u128::pow_radix(radix, len - 1));
+            let hi = end.min(u128::checked_pow_radix(radix, len).map_or(u128::MAX, |p| p - 1));
+            (len, lo, hi)
+        })
+        .collect())
 }
 
-fn calculate_multiplier(seed_len: u32, num_repeats: u32) -> u128 {
-    (0..num_repeats).fold(0u128, |acc, i| acc + pow10(i * seed_len))
+/// The multiplier `repunit(period, len / period)` and the run of `period`-digit seeds whose
+/// repetition lands in `lo..=hi`; the run is empty when `seed_lo > seed_hi`.
+fn seed_run(lo: u128, hi: u128, len: u32, period: u32, radix: u32) -> (u128, u128, u128) {
+    let multiplier = u128::repunit_radix(radix, period, len / period);
+    let seed_lo = u128::pow_radix(radix, period - 1).max(lo.div_ceil(multiplier));
+    let seed_hi = (u128::pow_radix(radix, period) - 1).min(hi / multiplier);
+    (multiplier, seed_lo, seed_hi)
+}
+
+/// Sum of the invalid IDs in `start..=end`, generated per digit length instead of visited.
+fn sum_invalid(start: u128, end: u128, radix: u32, repeats: Repeats) -> Result<u128> {
+    let mut total: u128 = 0;
+    for (len, lo, hi) in by_length(start, end, radix)? {
+        let sum = match repeats {
+            Repeats::Twice if len % 2 == 0 => sum_with_period(lo, hi, len, len / 2, radix)?,
+            Repeats::Twice => 0,
+            Repeats::AtLeastTwice => sum_any_period(lo, hi, len, radix)?,
+        };
+        total = total
+            .checked_add(sum)
+            .ok_or(anyhow!("Sum overflowed u128"))?;
+    }
+    Ok(total)
+}
+
+/// Sum of the `len`-digit numbers in `lo..=hi` made of one `period`-digit seed repeated, each
+/// being `seed * repunit(period, len / period)`; the valid seeds form one contiguous run.
+fn sum_with_period(lo: u128, hi: u128, len: u32, period: u32, radix: u32) -> Result<u128> {
+    let (multiplier, seed_lo, seed_hi) = seed_run(lo, hi, len, period, radix);
+    if seed_lo > seed_hi {
+        return Ok(0);
+    }
+
+    let count = seed_hi - seed_lo + 1;
+    let (a, b) = if count % 2 == 0 {
+        (count / 2, seed_lo + seed_hi)
+    } else {
+        (count, (seed_lo + seed_hi) / 2)
+    };
+    a.checked_mul(b)
+        .and_then(|seeds| seeds.checked_mul(multiplier))
+        .ok_or(anyhow!("Sum overflowed u128"))
+}
+
+/// Sum of the `len`-digit numbers in `lo..=hi` with any period that is a proper divisor of `len`.
+///
+/// Each number is counted once under its smallest period: the sum for exactly period `d` is the
+/// sum for period `d` minus the exact sums of the divisors of `d`. Every partial sum is bounded by
+/// the final one, so this only fails if the answer itself does not fit in a `u128`.
+fn sum_any_period(lo: u128, hi: u128, len: u32, radix: u32) -> Result<u128> {
+    let periods: Vec<u32> = len.divisors().into_iter().filter(|&d| d < len).collect();
+    let mut exact: Vec<u128> = Vec::with_capacity(periods.len());
+    for (i, &period) in periods.iter().enumerate() {
+        let shorter: u128 = (0..i)
+            .filter(|&j| period % periods[j] == 0)
+            .map(|j| exact[j])
+            .sum();
+        exact.push(sum_with_period(lo, hi, len, period, radix)? - shorter);
+    }
+    exact
+        .into_iter()
+        .try_fold(0u128, |acc, sum| acc.checked_add(sum))
+        .ok_or(anyhow!("Sum overflowed u128"))
+}
+
+/// An invalid ID split into its shortest repeated block, e.g. `2121212121 = "21" × 5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Invalid {
+    id: u128,
+    seed: u128,
+    repeats: u32,
+    radix: u32,
+}
+
+impl Invalid {
+    /// Whether the ID is some block written exactly twice, i.e. counts for Part 1. `1111` is
+    /// `"1" × 4` but also `"11" × 2`, so this holds whenever the minimal repeat count is even.
+    fn is_doubled(&self) -> bool {
+        self.repeats.is_multiple_of(2)
+    }
+}
+
+impl std::fmt::Display for Invalid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} = \"{}\" × {}",
+            self.id.to_radix_string(self.radix),
+            self.seed.to_radix_string(self.radix),
+            self.repeats
+        )
+    }
+}
+
+/// Length of the shortest block whose repetition spells `id` in `radix`; the full length if
+/// there is none.
+fn minimal_period(id: u128, radix: u32) -> u32 {
+    let len = id.digit_count_radix(radix);
+    // A repetition that does not fit in a u128 cannot be `id`, so overflow just rules `d` out.
+    len.divisors()
+        .into_iter()
+        .filter(|&d| d < len)
+        .find(|&d| {
+            let block = id % u128::pow_radix(radix, d);
+            u128::checked_repunit_radix(radix, d, len / d).and_then(|r| block.checked_mul(r))
+                == Some(id)
+        })
+        .unwrap_or(len)
+}
+
+/// Every invalid ID in `start..=end` in increasing order, each generated once from its minimal
+/// seed rather than found by scanning the range.
+fn invalid_ids(start: u128, end: u128, radix: u32) -> Result<Vec<Invalid>> {
+    let mut ids = vec![];
+    for (len, lo, hi) in by_length(start, end, radix)? {
+        for period in len.divisors().into_iter().filter(|&d| d < len) {
+            let (multiplier, seed_lo, seed_hi) = seed_run(lo, hi, len, period, radix);
+            ids.extend(
+                (seed_lo..=seed_hi)
+                    .filter(|&seed| minimal_period(seed * multiplier, radix) == period)
+                    .map(|seed| Invalid {
+                        id: seed * multiplier,
+                        seed,
+                        repeats: len / period,
+                        radix,
+                    }),
+            );
+        }
+    }
+    ids.sort_by_key(|invalid| invalid.id);
+    Ok(ids)
+}
+
+/// The invalid IDs of one input range with its Part 1 and Part 2 subtotals.
+#[derive(Debug, Clone)]
+struct RangeReport {
+    start: u128,
+    end: u128,
+    radix: u32,
+    ids: Vec<Invalid>,
+    part1: u128,
+    part2: u128,
+}
+
+impl std::fmt::Display for RangeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}-{}: part1 = {}, part2 = {}",
+            self.start.to_radix_string(self.radix),
+            self.end.to_radix_string(self.radix),
+            self.part1,
+            self.part2
+        )?;
+        for invalid in &self.ids {
+            writeln!(f, "  {invalid}")?;
+        }
+        std::fmt::Result::Ok(())
+    }
+}
+
+fn audit(ranges: &[(u128, u128)], radix: u32) -> Result<Vec<RangeReport>> {
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            let ids = invalid_ids(start, end, radix)?;
+            let sum = |doubled_only: bool| {
+                ids.iter()
+                    .filter(|i| !doubled_only || i.is_doubled())
+                    .try_fold(0u128, |acc, i| acc.checked_add(i.id))
+                    .ok_or(anyhow!("Sum overflowed u128"))
+            };
+            Ok(RangeReport {
+                start,
+                end,
+                radix,
+                part1: sum(true)?,
+                part2: sum(false)?,
+                ids,
+            })
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -165,8 +334,152 @@ mod tests {
     fn test_multiplier() {
         let seed_len = 2;
         let num_repeats = 3;
-        let multiplier = calculate_multiplier(seed_len, num_repeats);
-        println!("{:?}", multiplier);
+        let multiplier = u128::repunit(seed_len, num_repeats);
+        assert_eq!(10101, multiplier);
+        assert_eq!(121212, 12 * multiplier);
+    }
+
+    #[test]
+    fn test_sum_invalid() {
+        let ranges = TEST.trim().replace('\n', "");
+        let mut part1 = 0;
+        let mut part2 = 0;
+        for range in ranges.split(',').filter(|r| !r.is_empty()) {
+            let (a, b) = range.split_once('-').unwrap();
+            let (a, b) = (a.parse().unwrap(), b.parse().unwrap());
+            part1 += sum_invalid(a, b, 10, Repeats::Twice).unwrap();
+            part2 += sum_invalid(a, b, 10, Repeats::AtLeastTwice).unwrap();
+        }
+        assert_eq!(1227775554, part1);
+        assert_eq!(4174379265, part2);
+    }
+
+    #[test]
+    fn test_sum_invalid_brute_force() {
+        for (start, end) in [
+            (1, 20_000),
+            (95, 115),
+            (99_990, 1_001_100),
+            (123_123, 123_123),
+        ] {
+            let brute: u128 = (start..=end)
+                .filter(|&i| check_leetcode_459(i.to_string()))
+                .sum();
+            assert_eq!(
+                brute,
+                sum_invalid(start, end, 10, Repeats::AtLeastTwice).unwrap()
+            );
+
+            let brute: u128 = (start..=end)
+                .filter(|&i| {
+                    let s = i.to_string();
+                    s.len() % 2 == 0 && s[..s.len() / 2] == s[s.len() / 2..]
+                })
+                .sum();
+            assert_eq!(brute, sum_invalid(start, end, 10, Repeats::Twice).unwrap());
+        }
+
+        // Trillions of IDs per range, and the top of u128.
+        assert!(sum_invalid(1, 10u128.pow(24), 10, Repeats::AtLeastTwice).is_ok());
+        assert!(sum_invalid(0, u128::MAX, 10, Repeats::AtLeastTwice).is_err());
+        let threes = 3 * u128::repunit(1, 39);
+        let top = sum_invalid(threes - 1, threes + 1, 10, Repeats::AtLeastTwice).unwrap();
+        assert_eq!(threes, top);
+        assert_eq!(
+            0,
+            sum_invalid(threes, u128::MAX, 10, Repeats::Twice).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_ids() {
+        let ids = invalid_ids(2121212118, 2121212124, 10).unwrap();
+        assert_eq!(1, ids.len());
+        assert_eq!("2121212121 = \"21\" × 5", ids[0].to_string());
+
+        let ids = invalid_ids(1, 1_200, 10).unwrap();
+        let brute: Vec<u128> = (1..=1_200u128)
+            .filter(|&i| check_leetcode_459(i.to_string()))
+            .collect();
+        assert_eq!(brute, ids.iter().map(|i| i.id).collect::<Vec<_>>());
+        assert_eq!(
+            Invalid {
+                id: 1111,
+                seed: 1,
+                repeats: 4,
+                radix: 10
+            },
+            ids[ids.len() - 1]
+        );
+        assert!(ids[ids.len() - 1].is_doubled());
+
+        // 39 digits: repeating the last digit 39 times does not fit in a u128.
+        let id = 1_000_000_000_009 * u128::repunit(13, 3);
+        assert_eq!(13, minimal_period(id, 10));
+        assert_eq!(
+            vec![id],
+            invalid_ids(id, id, 10)
+                .unwrap()
+                .iter()
+                .map(|i| i.id)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(39, minimal_period(u128::MAX, 10));
+        let start = u128::MAX - 10u128.pow(25);
+        let top = invalid_ids(start, u128::MAX, 10).unwrap();
+        assert_eq!(
+            vec![3_402_823_669_209 * u128::repunit(13, 3)],
+            top.iter().map(|i| i.id).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            sum_invalid(start, u128::MAX, 10, Repeats::AtLeastTwice).unwrap(),
+            top.iter().map(|i| i.id).sum::<u128>()
+        );
+
+        for report in audit(&[(1, 20_000), (95, 115)], 10).unwrap() {
+            let (start, end) = (report.start, report.end);
+            assert_eq!(
+                sum_invalid(start, end, 10, Repeats::Twice).unwrap(),
+                report.part1
+            );
+            assert_eq!(
+                sum_invalid(start, end, 10, Repeats::AtLeastTwice).unwrap(),
+                report.part2
+            );
+        }
+    }
+
+    #[test]
+    fn test_radix() {
+        for radix in 2..=36 {
+            let end = 5_000;
+            let brute: Vec<u128> = (1..=end).filter(|&i| is_repeated(i, radix)).collect();
+            let ids = invalid_ids(1, end, radix).unwrap();
+            assert_eq!(brute, ids.iter().map(|i| i.id).collect::<Vec<_>>());
+            assert_eq!(
+                brute.iter().sum::<u128>(),
+                sum_invalid(1, end, radix, Repeats::AtLeastTwice).unwrap()
+            );
+            let doubled: u128 = brute
+                .iter()
+                .filter(|&&i| {
+                    let s = i.to_radix_string(radix);
+                    s.len() % 2 == 0 && s[..s.len() / 2] == s[s.len() / 2..]
+                })
+                .sum();
+            assert_eq!(doubled, sum_invalid(1, end, radix, Repeats::Twice).unwrap());
+        }
+
+        let ranges = read_ranges(BufReader::new("a-1f,FF-100\n".as_bytes()), 16).unwrap();
+        assert_eq!(vec![(10, 31), (255, 256)], ranges);
+        let reports = audit(&ranges, 16).unwrap();
+        assert_eq!(
+            "  11 = \"1\" × 2",
+            reports[0].to_string().lines().nth(1).unwrap()
+        );
+        assert_eq!(0x11 + 0xff, reports.iter().map(|r| r.part2).sum::<u128>());
+        assert!(read_ranges(BufReader::new("1-2".as_bytes()), 37).is_err());
+        assert!(sum_invalid(1, 2, 1, Repeats::Twice).is_err());
     }
 
     #[test]
@@ -183,11 +496,8 @@ mod tests {
 
     #[test]
     fn test_bit_num() {
-        let a = match 32u64.checked_ilog10() {
-            Some(exp) => exp + 1,
-            None => panic!("panic"),
-        };
-        println!("a={a}");
+        assert_eq!(2, 32u64.digit_count());
+        assert_eq!(1, 0u64.digit_count());
     }
 
     #[test]
@@ -1,10 +1,11 @@
+use adv_code_2025::digits::Digits;
 use adv_code_2025::*;
 use anyhow::*;
 use code_timing_macros::time_snippet;
 use const_format::concatcp;
+use rayon::prelude::*;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::ops::Mul;
+use std::io::{BufRead, BufReader, Read, Write};
 
 const DAY: &str = "03";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
@@ -30,8 +31,8 @@ fn main() -> Result<()> {
     //         if line.trim().is_empty() {
     //             continue;
     //         }
-    //         let jolts = find_maximum_jolts(line.trim().as_bytes(), 2);
-    //         answer += jolts as usize;
+    //         let jolts = find_maximum_jolts(line.trim().as_bytes(), 2)?.value()?;
+    //         answer += usize::try_from(jolts)?;
     //     }
     //
     //     Ok(answer)
@@ -55,8 +56,8 @@ fn main() -> Result<()> {
             if line.trim().is_empty() {
                 continue;
             }
-            let jolts = find_maximum_jolts(line.trim().as_bytes(), 12);
-            answer += jolts as usize;
+            let jolts = find_maximum_jolts(line.trim().as_bytes(), 12)?.value()?;
+            answer += usize::try_from(jolts)?;
         }
 
         Ok(answer)
@@ -69,51 +70,446 @@ fn main() -> Result<()> {
     println!("Result = {}", result);
     //endregion
 
+    //region Parallel
+    println!("\n=== Parallel ===");
+
+    assert_eq!(
+        3121910778619,
+        total_parallel(TEST.as_bytes(), 12, 16, None)?
+    );
+
+    let input_file = File::open(INPUT_FILE)?;
+    let parallel = time_snippet!(total_parallel(input_file, 12, 1 << 20, None)?);
+    ensure!(
+        parallel == result as u128,
+        "parallel total {parallel} != {result}"
+    );
+    println!("Result = {}", parallel);
+    //endregion
+
+    //region Cap sweep
+    println!("\n=== Cap sweep ===");
+
+    /// Total joltage over all banks for caps `1..=caps`, `totals[k - 1]` for cap `k`.
+    fn cap_totals<R: BufRead>(reader: R, caps: usize) -> Result<Vec<u128>> {
+        let mut totals = vec![0u128; caps];
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let values = JoltageSweep::new(line.trim().as_bytes())
+                .and_then(|sweep| sweep.values(caps))
+                .with_context(|| format!("line {}", i + 1))?;
+            for (cap, (total, value)) in (1..).zip(totals.iter_mut().zip(values)) {
+                *total = total
+                    .checked_add(value)
+                    .ok_or(anyhow!("cap {cap} total overflows u128"))?;
+            }
+        }
+        Ok(totals)
+    }
+
+    let totals = cap_totals(BufReader::new(TEST.as_bytes()), 15)?;
+    assert_eq!(357, totals[1]);
+    assert_eq!(3121910778619, totals[11]);
+
+    // 30-digit joltages leave room in a u128 to add up hundreds of millions of banks.
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let totals = time_snippet!(cap_totals(input_file, 30)?);
+    for (cap, total) in (1..).zip(&totals) {
+        println!("cap {cap:>2}: {total}");
+    }
+    //endregion
+
     Ok(())
 }
 
+/// The digits kept from a bank, as positions into it and their values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Selection {
+    indices: Vec<usize>,
+    digits: Vec<u8>,
+}
+
+impl Selection {
+    /// The selected digits as a number, or an error once it no longer fits in a `u128`
+    /// (more than 38 digits); `Display` prints any length.
+    fn value(&self) -> Result<u128> {
+        self.digits
+            .iter()
+            .try_fold(0u128, |acc, &d| acc.checked_push_digit(d))
+            .ok_or(anyhow!(
+                "{}-digit joltage overflows u128",
+                self.digits.len()
+            ))
+    }
+}
+
+impl std::fmt::Display for Selection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for d in &self.digits {
+            write!(f, "{d}")?;
+        }
+        std::fmt::Result::Ok(())
+    }
+}
+
 // 818181911112111 -> 92
-fn find_maximum_jolts(bytes: &[u8], cap: usize) -> usize {
-    let mut stack: Vec<u8> = vec![];
+/// The largest `cap`-digit subsequence of `bytes`, in one pass: each digit pops the smaller
+/// digits before it as long as enough digits remain to still fill `cap` places.
+fn find_maximum_jolts(bytes: &[u8], cap: usize) -> Result<Selection> {
     let len = bytes.len();
-    for (index, b) in bytes.iter().enumerate() {
-        while !stack.is_empty() && b > stack.last().unwrap() {
-            if stack.len() + len - 1 - index < cap {
+    ensure!(cap <= len, "cannot pick {cap} digits from a bank of {len}");
+    let bank = parse_bank(bytes)?;
+    let mut stack: Vec<usize> = Vec::with_capacity(cap);
+    for (index, &b) in bank.iter().enumerate() {
+        while let Some(&top) = stack.last() {
+            if b <= bank[top] || stack.len() - 1 + len - index < cap {
                 break;
             }
             stack.pop();
         }
-        stack.push(*b);
+        if stack.len() < cap {
+            stack.push(index);
+        }
     }
 
-    let mut ans = 0;
-    for b in (1..=cap).rev() {
-        ans += 10usize
-            .pow(b as u32 - 1)
-            .mul((stack.remove(0) - b'0') as usize);
+    let digits = stack.iter().map(|&i| bank[i]).collect();
+    Ok(Selection {
+        indices: stack,
+        digits,
+    })
+}
+
+/// The digit values of one bank line.
+fn parse_bank(bytes: &[u8]) -> Result<Vec<u8>> {
+    bytes
+        .iter()
+        .enumerate()
+        .map(|(index, &b)| {
+            ensure!(
+                b.is_ascii_digit(),
+                "invalid byte {:?} at index {index}",
+                b as char
+            );
+            Ok(b - b'0')
+        })
+        .collect()
+}
+
+/// Splits a byte stream into chunks of whole lines, each at least `chunk_size` bytes unless it
+/// is the last. Only the current chunk and the partial line after it are held in memory.
+struct LineChunks<R> {
+    reader: R,
+    chunk_size: usize,
+    carry: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: Read> LineChunks<R> {
+    fn new(reader: R, chunk_size: usize) -> Self {
+        Self {
+            reader,
+            chunk_size: chunk_size.max(1),
+            carry: vec![],
+            eof: false,
+        }
     }
+}
 
-    ans
+impl<R: Read> Iterator for LineChunks<R> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = std::mem::take(&mut self.carry);
+        let mut scanned = 0;
+        loop {
+            if chunk.len() >= self.chunk_size || self.eof {
+                // Cut after the last newline; a line longer than the chunk keeps growing it.
+                if let Some(cut) = chunk[scanned..].iter().rposition(|&b| b == b'\n') {
+                    self.carry = chunk.split_off(scanned + cut + 1);
+                    return Some(Ok(chunk));
+                }
+                if self.eof {
+                    return (!chunk.is_empty()).then_some(Ok(chunk));
+                }
+                scanned = chunk.len();
+            }
+
+            let start = chunk.len();
+            chunk.resize(start + self.chunk_size, 0);
+            match self.reader.read(&mut chunk[start..]) {
+                Result::Ok(n) => {
+                    chunk.truncate(start + n);
+                    self.eof = n == 0;
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => chunk.truncate(start),
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+/// Total joltage for `cap` over every bank, reading `chunk_size`-byte chunks of whole lines and
+/// solving the banks of each chunk in parallel. If `output` is given, each bank's selection is
+/// written to it as `line<TAB>digits`, in input order.
+fn total_parallel<R: Read>(
+    reader: R,
+    cap: usize,
+    chunk_size: usize,
+    mut output: Option<&mut dyn Write>,
+) -> Result<u128> {
+    let mut total: u128 = 0;
+    let mut first_line = 1;
+    for chunk in LineChunks::new(reader, chunk_size) {
+        let chunk = chunk?;
+        let lines: Vec<&[u8]> = chunk.split(|&b| b == b'\n').collect();
+        let selections: Vec<Option<Selection>> = lines
+            .par_iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let bank = line.trim_ascii();
+                if bank.is_empty() {
+                    return Ok(None);
+                }
+                find_maximum_jolts(bank, cap)
+                    .map(Some)
+                    .with_context(|| format!("line {}", first_line + i))
+            })
+            .collect::<Result<_>>()?;
+
+        for (i, selection) in selections.iter().enumerate() {
+            let Some(selection) = selection else {
+                continue;
+            };
+            total = total
+                .checked_add(selection.value()?)
+                .ok_or(anyhow!("total joltage overflows u128"))?;
+            if let Some(out) = output.as_mut() {
+                writeln!(out, "{}\t{}", first_line + i, selection)?;
+            }
+        }
+        // The chunk ends in a newline, so the last split piece is not a line of its own.
+        first_line += lines.len() - 1;
+    }
+    Ok(total)
+}
+
+/// Optimal selections for every cap of one bank at once.
+///
+/// Deleting the first digit smaller than its successor (or the last digit if there is none)
+/// always leaves the best selection one digit shorter, so repeating that from the full bank
+/// visits every cap in turn. `rank[i]` records when digit `i` went; the best `k` digits are
+/// those removed last, i.e. with `rank[i] >= len - k`.
+#[derive(Debug, Clone)]
+struct JoltageSweep {
+    bank: Vec<u8>,
+    rank: Vec<usize>,
+}
+
+impl JoltageSweep {
+    /// O(n): the scan only steps back one digit after each deletion.
+    fn new(bytes: &[u8]) -> Result<Self> {
+        let len = bytes.len();
+        let bank = parse_bank(bytes)?;
+
+        // Doubly linked list over the surviving digits, `len` standing for "none".
+        let mut prev: Vec<usize> = (0..len).map(|i| i.checked_sub(1).unwrap_or(len)).collect();
+        let mut next: Vec<usize> = (1..=len).collect();
+        let mut rank = vec![0; len];
+        let mut head = 0;
+        let mut cur = 0;
+        for step in 0..len {
+            while next[cur] < len && bank[cur] >= bank[next[cur]] {
+                cur = next[cur];
+            }
+            rank[cur] = step;
+            let (p, n) = (prev[cur], next[cur]);
+            if p < len {
+                next[p] = n;
+            } else {
+                head = n;
+            }
+            if n < len {
+                prev[n] = p;
+            }
+            cur = if p < len { p } else { head };
+        }
+
+        Ok(Self { bank, rank })
+    }
+
+    fn len(&self) -> usize {
+        self.bank.len()
+    }
+
+    /// The best `cap` digits, the same as `find_maximum_jolts(bank, cap)`.
+    fn selection(&self, cap: usize) -> Result<Selection> {
+        let len = self.len();
+        ensure!(cap <= len, "cannot pick {cap} digits from a bank of {len}");
+        let indices: Vec<usize> = (0..len).filter(|&i| self.rank[i] >= len - cap).collect();
+        let digits = indices.iter().map(|&i| self.bank[i]).collect();
+        Ok(Selection { indices, digits })
+    }
+
+    /// Selections for caps `1..=len`, in order. Each is rebuilt from `rank` in O(n), so all of
+    /// them together take O(n²).
+    fn selections(&self) -> impl Iterator<Item = Selection> + '_ {
+        (1..=self.len()).map(|cap| self.selection(cap).unwrap())
+    }
+
+    /// The joltage for caps `1..=caps`, entry `k - 1` for cap `k`. Up to 38 digits always fit
+    /// in a `u128`; a longer cap errors as soon as its value does not, as does a cap longer
+    /// than the bank.
+    fn values(&self, caps: usize) -> Result<Vec<u128>> {
+        let len = self.len();
+        ensure!(
+            caps <= len,
+            "cannot pick {caps} digits from a bank of {len}"
+        );
+        self.selections()
+            .take(caps)
+            .map(|selection| selection.value())
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use adv_code_2025::rng::Rng;
+
+    fn jolts(bytes: &[u8], cap: usize) -> u128 {
+        find_maximum_jolts(bytes, cap).unwrap().value().unwrap()
+    }
 
     #[test]
     fn test_find_2_bit_maximum_jolts() {
-        assert_eq!(98, find_maximum_jolts(b"987654321111111", 2));
-        assert_eq!(89, find_maximum_jolts(b"811111111111119", 2));
-        assert_eq!(78, find_maximum_jolts(b"234234234234278", 2));
-        assert_eq!(92, find_maximum_jolts(b"818181911112111", 2));
+        assert_eq!(98, jolts(b"987654321111111", 2));
+        assert_eq!(89, jolts(b"811111111111119", 2));
+        assert_eq!(78, jolts(b"234234234234278", 2));
+        assert_eq!(92, jolts(b"818181911112111", 2));
     }
 
     #[test]
     fn test_find_12_bit_maximum_jolts() {
-        assert_eq!(987654321111, find_maximum_jolts(b"987654321111111", 12));
-        assert_eq!(811111111119, find_maximum_jolts(b"811111111111119", 12));
-        assert_eq!(434234234278, find_maximum_jolts(b"234234234234278", 12));
-        assert_eq!(888911112111, find_maximum_jolts(b"818181911112111", 12));
+        assert_eq!(987654321111, jolts(b"987654321111111", 12));
+        assert_eq!(811111111119, jolts(b"811111111111119", 12));
+        assert_eq!(434234234278, jolts(b"234234234234278", 12));
+        assert_eq!(888911112111, jolts(b"818181911112111", 12));
+    }
+
+    #[test]
+    fn test_selection() {
+        let selection = find_maximum_jolts(b"818181911112111", 2).unwrap();
+        assert_eq!(vec![6, 11], selection.indices);
+        assert_eq!("92", selection.to_string());
+
+        assert!(find_maximum_jolts(b"12a4", 2).is_err());
+        assert!(find_maximum_jolts(b"12/4", 2).is_err());
+        assert!(find_maximum_jolts(b"12", 3).is_err());
+        assert_eq!("", find_maximum_jolts(b"", 0).unwrap().to_string());
+    }
+
+    #[test]
+    fn test_long_bank() {
+        // Pick the best digit within reach for each place, O(n * cap).
+        fn greedy(bytes: &[u8], cap: usize) -> String {
+            let mut from = 0;
+            (0..cap)
+                .map(|place| {
+                    let window = &bytes[from..bytes.len() - (cap - 1 - place)];
+                    let best = *window.iter().max().unwrap();
+                    from += window.iter().position(|&b| b == best).unwrap() + 1;
+                    best as char
+                })
+                .collect()
+        }
+
+        let mut rng = Rng::new(3);
+        let bank: Vec<u8> = (0..20_000).map(|_| b'0' + rng.below(10) as u8).collect();
+        for cap in [1, 2, 12, 40, 1_000] {
+            let selection = find_maximum_jolts(&bank, cap).unwrap();
+            assert_eq!(greedy(&bank, cap), selection.to_string());
+            assert!(selection.indices.windows(2).all(|w| w[0] < w[1]));
+        }
+        assert!(find_maximum_jolts(&bank, 40).unwrap().value().is_err());
+    }
+
+    #[test]
+    fn test_sweep() {
+        let sweep = JoltageSweep::new(b"818181911112111").unwrap();
+        let values = sweep.values(15).unwrap();
+        assert_eq!(92, values[1]);
+        assert_eq!(888911112111, values[11]);
+        assert_eq!(
+            "cannot pick 16 digits from a bank of 15",
+            sweep.values(16).unwrap_err().to_string()
+        );
+
+        let nines = JoltageSweep::new(&[b'9'; 40]).unwrap();
+        assert_eq!(10u128.pow(38) - 1, nines.values(38).unwrap()[37]);
+        assert_eq!(
+            "39-digit joltage overflows u128",
+            nines.values(40).unwrap_err().to_string()
+        );
+
+        let mut rng = Rng::new(39);
+        let banks: Vec<Vec<u8>> = (0..50)
+            .map(|_| (0..60).map(|_| b'0' + rng.below(10) as u8).collect())
+            .chain([b"12".to_vec(), b"111".to_vec(), b"54321".to_vec(), vec![]])
+            .collect();
+        for bank in banks {
+            let sweep = JoltageSweep::new(&bank).unwrap();
+            for (cap, selection) in (1..).zip(sweep.selections()) {
+                assert_eq!(find_maximum_jolts(&bank, cap).unwrap(), selection);
+            }
+        }
+        let err = JoltageSweep::new(b"1-2").err().unwrap().to_string();
+        assert_eq!("invalid byte '-' at index 1", err);
+        assert_eq!(err, find_maximum_jolts(b"1-2", 1).unwrap_err().to_string());
+    }
+
+    #[test]
+    fn test_parallel() {
+        let mut rng = Rng::new(26);
+        let banks: String = (0..500u64)
+            .map(|seed| {
+                let len = 12 + rng.below(40);
+                let bank: String = (0..len)
+                    .map(|_| char::from(b'0' + rng.below(10) as u8))
+                    .collect();
+                if seed % 50 == 0 {
+                    format!("{bank}\r\n\n")
+                } else {
+                    format!("{bank}\n")
+                }
+            })
+            .collect();
+
+        let serial: u128 = banks
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| jolts(l.trim().as_bytes(), 12))
+            .sum();
+        for chunk_size in [1, 7, 64, 4096, 1 << 20] {
+            let mut out = vec![];
+            let total = total_parallel(banks.as_bytes(), 12, chunk_size, Some(&mut out)).unwrap();
+            assert_eq!(serial, total);
+            let out = String::from_utf8(out).unwrap();
+            assert_eq!(500, out.lines().count());
+            assert!(out.starts_with("1\t"));
+            assert!(out.lines().nth(1).unwrap().starts_with("3\t"));
+        }
+
+        let mut no_newline = TEST.trim_end().as_bytes();
+        assert_eq!(
+            3121910778619,
+            total_parallel(&mut no_newline, 12, 5, None).unwrap()
+        );
+        let err = total_parallel("123\n45x\n".as_bytes(), 2, 3, None).unwrap_err();
+        assert_eq!("line 2", err.to_string());
     }
 
     #[test]
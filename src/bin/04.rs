@@ -1,10 +1,12 @@
+use adv_code_2025::peeling::{
+    parse_grid, parse_packed, peel, peel_packed, Boundary, Neighbourhood, Rules,
+};
 use adv_code_2025::*;
 use anyhow::*;
 use code_timing_macros::time_snippet;
 use const_format::concatcp;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::ops::Sub;
 
 const DAY: &str = "04";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
@@ -86,26 +88,8 @@ fn main() -> Result<()> {
     println!("\n=== Part 2 ===");
 
     fn part2<R: BufRead>(reader: R) -> Result<usize> {
-        let mut matrix = vec![];
-        reader
-            .lines()
-            .map(|l| l.unwrap())
-            .filter(|line| !line.is_empty())
-            .for_each(|line| {
-                let row: Vec<u8> = line.chars().map(|c| if c == '@' { 1 } else { 0 }).collect();
-                matrix.push(row);
-            });
-
-        let mut answer = 0;
-        loop {
-            let t = solve(&mut matrix);
-            if (t == 0) {
-                break;
-            }
-            answer += t;
-        }
-
-        Ok(answer)
+        let matrix = parse_grid(reader)?;
+        Ok(peel(&matrix, &Rules::default()).total)
     }
 
     assert_eq!(43, part2(BufReader::new(TEST.as_bytes()))?);
@@ -115,43 +99,107 @@ fn main() -> Result<()> {
     println!("Result = {}", result);
     //endregion
 
+    //region Waves
+    println!("\n=== Waves ===");
+
+    let peeling = peel(
+        &parse_grid(BufReader::new(TEST.as_bytes()))?,
+        &Rules::default(),
+    );
+    assert_eq!(vec![13, 12, 7, 5, 2, 1, 1, 1, 1], peeling.wave_counts);
+    print!("{}", peeling.heatmap());
+    print!("{}", peeling.to_csv());
+
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let peeling = time_snippet!(peel(&parse_grid(input_file)?, &Rules::default()));
+    print!("{}", peeling.waves_csv());
+    //endregion
+
+    //region Bit-packed
+    println!("\n=== Bit-packed ===");
+
+    let packed = parse_packed(BufReader::new(TEST.as_bytes()))?;
+    assert_eq!(43, peel_packed(&packed, 4).total);
+
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let packed = parse_packed(input_file)?;
+    let peeling = time_snippet!(peel_packed(&packed, 4));
+    ensure!(
+        peeling.total == result,
+        "packed total {} != {result}",
+        peeling.total
+    );
+    println!("Result = {}", peeling.total);
+
+    //endregion
+
+    //region Rule variants
+    println!("\n=== Rule variants ===");
+
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let matrix = parse_grid(input_file)?;
+    for (neighbourhood, threshold) in [
+        (Neighbourhood::VonNeumann, 2),
+        (Neighbourhood::Moore, 4),
+        (Neighbourhood::Radius(2), 12),
+    ] {
+        for boundary in [Boundary::Finite, Boundary::Toroidal] {
+            let rules = Rules {
+                threshold,
+                neighbourhood,
+                boundary,
+            };
+            let peeling = peel(&matrix, &rules);
+            println!(
+                "{neighbourhood:?} < {threshold}, {boundary:?}: {} removed in {} waves",
+                peeling.total,
+                peeling.wave_counts.len()
+            );
+        }
+    }
+    //endregion
+
     Ok(())
 }
 
-fn solve(matrix: &mut Vec<Vec<u8>>) -> usize {
-    let col_count = matrix[0].len();
-    let row_count = matrix.len();
-    let mut count_around = vec![vec![0; col_count]; row_count];
-
-    for r in 0..row_count {
-        for c in 0..col_count {
-            // r, c + 1
-            // r, c - 1
-            count_around[r][c] = matrix[r][c];
-            if c > 0 {
-                count_around[r][c] += matrix[r][c - 1];
-            }
-            if c + 1 < col_count {
-                count_around[r][c] += matrix[r][c + 1];
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adv_code_2025::bitgrid::BitGrid;
+    use adv_code_2025::rng::Rng;
+
+    /// A pseudo-random grid with about 70% rolls.
+    fn generate(rows: usize, cols: usize, seed: u64) -> BitGrid {
+        let mut grid = BitGrid::new(rows, cols);
+        let mut rng = Rng::new(seed);
+        for r in 0..rows {
+            for c in 0..cols {
+                grid.set(r, c, rng.chance(7, 10));
             }
         }
+        grid
     }
-    let mut answer = 0;
-    // around if small than 4
-    for r in 0..row_count {
-        for c in 0..col_count {
-            let mut t = count_around[r][c] as i16;
-            if r > 0 {
-                t += count_around[r - 1][c] as i16;
-            }
-            if r + 1 < row_count {
-                t += count_around[r + 1][c] as i16;
-            }
-            if t - 1 < 4 && matrix[r][c] == 1 {
-                matrix[r][c] = 0;
-                answer += 1;
-            }
-        }
+
+    #[test]
+    fn test_generated() {
+        let generated = generate(300, 500, 3);
+        assert_eq!((300, 500), (generated.rows(), generated.cols()));
+        assert_eq!(generated, generate(300, 500, 3));
+        assert_ne!(generated, generate(300, 500, 4));
+        let rolls = generated.count_ones() as f64 / (300.0 * 500.0);
+        assert!((0.65..0.75).contains(&rolls), "{rolls}");
+    }
+
+    #[test]
+    #[ignore = "peels a 10k x 10k grid both ways; run with --release"]
+    fn test_generated_packed() {
+        // 12.56 MB packed against 100 MB for the bytes alone.
+        let generated = generate(10_000, 10_000, 7);
+        assert_eq!(12_560_000, generated.memory());
+        let packed = peel_packed(&generated, 4);
+        let peeling = peel(&generated.to_rows(), &Rules::default());
+        assert_eq!(peeling.total, packed.total);
+        assert_eq!(peeling.wave_counts, packed.wave_counts);
+        assert_eq!(peeling.stable, packed.stable.to_rows());
     }
-    answer
 }
@@ -0,0 +1,143 @@
+use adv_code_2025::peeling::{parse_grid, peel, Peeling, Rules};
+use adv_code_2025::terminal::{CLEAR, RESET};
+use anyhow::*;
+use std::fs::File;
+use std::io::{stdout, BufReader, BufWriter, Write};
+use std::thread;
+use std::time::Duration;
+
+const TEST: &str = "\
+..@@.@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@@@@.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@.
+";
+
+const USAGE: &str = "usage: 04_visualizer [--delay MS] [--headless FILE] [INPUT]";
+
+const ROLL: &str = "\x1b[32m"; // Green
+const REMOVING: &str = "\x1b[91m"; // Bright Red
+const GONE: &str = "\x1b[90m"; // Grey
+
+struct Options {
+    delay: Duration,
+    headless: Option<String>,
+    input: Option<String>,
+}
+
+fn parse_args() -> Result<Options> {
+    let mut options = Options {
+        delay: Duration::from_millis(300),
+        headless: None,
+        input: None,
+    };
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--delay" => {
+                let ms = args
+                    .next()
+                    .ok_or(anyhow!("--delay needs a value\n{USAGE}"))?;
+                options.delay = Duration::from_millis(ms.parse()?);
+            }
+            "--headless" => {
+                options.headless = Some(
+                    args.next()
+                        .ok_or(anyhow!("--headless needs a file\n{USAGE}"))?,
+                );
+            }
+            "-h" | "--help" => bail!(USAGE),
+            _ if arg.starts_with("--") => bail!("unknown option {arg}\n{USAGE}"),
+            _ => options.input = Some(arg),
+        }
+    }
+    Ok(options)
+}
+
+/// The grid as it looks during wave `wave` (0 is the starting grid): rolls still standing,
+/// rolls going in this wave highlighted, and the ones already gone greyed out.
+fn render_frame(peeling: &Peeling, wave: usize) -> String {
+    let waves = peeling.wave_counts.len();
+    let removing = wave.checked_sub(1).map_or(0, |w| peeling.wave_counts[w]);
+    let gone: usize = peeling.wave_counts[..wave].iter().sum();
+    let standing =
+        peeling.total + peeling.stable.iter().flatten().filter(|&&v| v == 1).count() - gone;
+
+    let mut frame = String::from(CLEAR);
+    frame.push_str(&format!(
+        "Wave {wave}/{waves}: removing {removing}, {standing} left after this wave\n"
+    ));
+    for (r, row) in peeling.removed_in.iter().enumerate() {
+        for (c, removed_in) in row.iter().enumerate() {
+            let cell = match removed_in {
+                Some(w) if *w < wave => format!("{GONE}·{RESET}"),
+                Some(w) if *w == wave => format!("{REMOVING}@{RESET}"),
+                Some(_) => format!("{ROLL}@{RESET}"),
+                None if peeling.stable[r][c] == 1 => format!("{ROLL}@{RESET}"),
+                None => ".".to_string(),
+            };
+            frame.push_str(&cell);
+        }
+        frame.push('\n');
+    }
+    frame
+}
+
+fn main() -> Result<()> {
+    let options = parse_args()?;
+    let matrix = match &options.input {
+        Some(path) => parse_grid(BufReader::new(File::open(path)?))?,
+        None => parse_grid(TEST.as_bytes())?,
+    };
+    let peeling = peel(&matrix, &Rules::default());
+    let frames = (0..=peeling.wave_counts.len()).map(|wave| render_frame(&peeling, wave));
+
+    match &options.headless {
+        Some(path) => {
+            let mut out = BufWriter::new(File::create(path)?);
+            for frame in frames {
+                out.write_all(frame.as_bytes())?;
+            }
+            out.flush()?;
+            println!("Wrote {} frames to {path}", peeling.wave_counts.len() + 1);
+        }
+        None => {
+            for frame in frames {
+                print!("{frame}");
+                stdout().flush()?;
+                thread::sleep(options.delay);
+            }
+            println!("Removed {} rolls.", peeling.total);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_frame() {
+        let peeling = peel(&parse_grid(TEST.as_bytes()).unwrap(), &Rules::default());
+        let first = render_frame(&peeling, 0);
+        assert!(first.starts_with(CLEAR));
+        assert!(first.contains("Wave 0/9: removing 0, 71 left"));
+        assert!(!first.contains(REMOVING));
+
+        let wave1 = render_frame(&peeling, 1);
+        assert!(wave1.contains("removing 13, 58 left"));
+        assert_eq!(13, wave1.matches(REMOVING).count());
+
+        let last = render_frame(&peeling, 9);
+        assert_eq!(42, last.matches('·').count());
+        assert_eq!(28, last.matches(ROLL).count());
+    }
+}
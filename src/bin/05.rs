@@ -1,8 +1,8 @@
+use adv_code_2025::ranges::{Inclusive, RangeReader};
 use adv_code_2025::*;
 use anyhow::*;
 use code_timing_macros::time_snippet;
 use const_format::concatcp;
-use std::cmp::max;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
@@ -23,105 +23,47 @@ const TEST: &str = "\
 32
 ";
 
-#[derive(Debug, Copy, Clone)]
-struct Range {
-    start: usize,
-    end: usize,
-}
+fn main() -> Result<()> {
+    start_day(DAY);
 
-#[allow(dead_code)]
-impl Range {
-    fn is_within(&self, o: usize) -> bool {
-        o >= self.start && o <= self.end
+    //region Part 1
+    println!("=== Part 1 ===");
+
+    fn part1<R: BufRead>(mut reader: R) -> Result<usize> {
+        let index = FreshIndex::new(read_ranges(&mut reader)?);
+        let ids = read_ids(reader)?;
+        Ok(ids.iter().filter(|&&id| index.contains(id)).count())
     }
 
-    fn count(&self) -> usize {
-        self.end - self.start + 1
+    assert_eq!(3, part1(BufReader::new(TEST.as_bytes()))?);
+
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let result = time_snippet!(part1(input_file)?);
+    println!("Result = {}", result);
+    //endregion
+
+    //region Batch
+    println!("\n=== Batch ===");
+
+    fn batch<R: BufRead>(mut reader: R) -> Result<usize> {
+        let index = FreshIndex::new(read_ranges(&mut reader)?);
+        let ids = read_ids(reader)?;
+        Ok(index.count_fresh(&ids))
     }
-}
 
-fn main() -> Result<()> {
-    start_day(DAY);
+    assert_eq!(3, batch(BufReader::new(TEST.as_bytes()))?);
 
-    //region Part 1
-    // println!("=== Part 1 ===");
-    //
-    // fn part1<R: BufRead>(reader: R) -> Result<usize> {
-    //     let mut lines = reader.lines();
-    //     let mut ranges = vec![];
-    //     let mut answer = 0;
-    //     while let Some(x) = lines.next() {
-    //         let x = x?;
-    //         if x.trim().is_empty() {
-    //             break;
-    //         }
-    //         let (a, b) = x
-    //             .trim()
-    //             .split_once('-')
-    //             .ok_or(anyhow!("Invalid input format"))?;
-    //         let start: usize = a.parse()?;
-    //         let end: usize = b.parse()?;
-    //         let range = Range { start, end };
-    //         ranges.push(range);
-    //     }
-    //     for line in lines {
-    //         let line = line?;
-    //         if line.trim().is_empty() {
-    //             continue;
-    //         }
-    //         let num: usize = line.trim().parse()?;
-    //         if ranges.iter().any(|r| r.is_within(num)) {
-    //             answer += 1;
-    //         }
-    //     }
-    //
-    //     Ok(answer)
-    // }
-    //
-    // assert_eq!(3, part1(BufReader::new(TEST.as_bytes()))?);
-    //
-    // let input_file = BufReader::new(File::open(INPUT_FILE)?);
-    // let result = time_snippet!(part1(input_file)?);
-    // println!("Result = {}", result);
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let fresh = time_snippet!(batch(input_file)?);
+    ensure!(fresh == result, "batch count {fresh} != {result}");
+    println!("Result = {}", fresh);
     //endregion
 
     //region Part 2
     println!("\n=== Part 2 ===");
 
-    fn part2<R: BufRead>(reader: R) -> Result<usize> {
-        let lines = reader.lines();
-        let mut ranges: Vec<Range> = vec![];
-        for x in lines {
-            let x = x?;
-            if x.trim().is_empty() {
-                break;
-            }
-            let (a, b) = x
-                .trim()
-                .split_once('-')
-                .ok_or(anyhow!("Invalid input format"))?;
-            let start: usize = a.parse()?;
-            let end: usize = b.parse()?;
-            let range = Range { start, end };
-
-            ranges.push(range);
-        }
-
-        ranges.sort_by_key(|r| r.start);
-
-        let mut max_i = 0;
-        let mut ans = 0;
-        for Range { start, end } in ranges {
-            // the max_i means the previous end index add 1, so current end could be equaled
-            if end >= max_i {
-                println!("end: {end}, start: {}", max(start, max_i));
-                ans += end - max(start, max_i) + 1;
-                // +1 because the end has been added
-                max_i = end + 1;
-            }
-        }
-
-        Ok(ans)
+    fn part2<R: BufRead>(mut reader: R) -> Result<u128> {
+        FreshIndex::new(read_ranges(&mut reader)?).fresh_count()
     }
 
     assert_eq!(14, part2(BufReader::new(TEST.as_bytes()))?);
@@ -131,5 +73,314 @@ fn main() -> Result<()> {
     println!("Result = {}", result);
     //endregion
 
+    //region Export
+    println!("\n=== Export ===");
+
+    fn report<R: BufRead>(mut reader: R) -> Result<FreshReport> {
+        let ranges = read_ranges(&mut reader)?;
+        let ids = read_ids(reader)?;
+        Ok(FreshReport::new(ranges, &ids))
+    }
+
+    let test_report = report(BufReader::new(TEST.as_bytes()))?;
+    print!("{}", test_report.to_text());
+    println!("{}", test_report.to_json());
+
+    let input_file = BufReader::new(File::open(INPUT_FILE)?);
+    let input_report = time_snippet!(report(input_file)?);
+    println!(
+        "{} ranges merged into {}",
+        input_report.ranges.len(),
+        input_report.merged.len()
+    );
+
+    // The full export is long, so it only goes to files: `cargo run --bin 05 -- --export PREFIX`.
+    let mut args = std::env::args().skip(1);
+    match (args.next().as_deref(), args.next()) {
+        (Some("--export"), Some(prefix)) => {
+            std::fs::write(format!("{prefix}.txt"), input_report.to_text())?;
+            std::fs::write(format!("{prefix}.json"), input_report.to_json())?;
+            println!("Wrote {prefix}.txt and {prefix}.json");
+        }
+        (None, _) => println!("Pass --export PREFIX to write PREFIX.txt and PREFIX.json"),
+        _ => bail!("usage: 05 [--export PREFIX]"),
+    }
+    //endregion
+
     Ok(())
 }
+
+/// The range section, up to the blank line; `reader` is left at the first ID.
+fn read_ranges<R: BufRead>(reader: &mut R) -> Result<Vec<Inclusive>> {
+    RangeReader::new(reader)
+        .allow_overlaps()
+        .stop_at_blank_line()
+        .map(|token| token.map(|t| t.inclusive()))
+        .collect()
+}
+
+fn read_ids<R: BufRead>(reader: R) -> Result<Vec<u128>> {
+    let mut ids = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        ids.push(line.trim().parse()?);
+    }
+    Ok(ids)
+}
+
+/// The fresh ranges merged into sorted, disjoint, non-adjacent ranges, so an ID is fresh iff the
+/// last range starting at or before it also ends at or after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FreshIndex {
+    merged: Vec<Inclusive>,
+}
+
+impl FreshIndex {
+    fn new(mut ranges: Vec<Inclusive>) -> Self {
+        ranges.sort();
+        let mut merged: Vec<Inclusive> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                // Touching ranges merge too: 3-5 and 6-8 become 3-8.
+                Some(last) if last.touches(&range) => *last = last.hull(&range),
+                _ => merged.push(range),
+            }
+        }
+        Self { merged }
+    }
+
+    /// O(log ranges).
+    fn contains(&self, id: u128) -> bool {
+        let after = self.merged.partition_point(|r| r.start() <= id);
+        after > 0 && self.merged[after - 1].contains(id)
+    }
+
+    /// How many of `ids` are fresh, by sorting them and walking them alongside the ranges:
+    /// O(ids log ids + ranges) instead of a search per ID.
+    fn count_fresh(&self, ids: &[u128]) -> usize {
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        let mut ranges = self.merged.iter().peekable();
+        let mut fresh = 0;
+        for id in ids {
+            while ranges.next_if(|r| r.end() < id).is_some() {}
+            match ranges.peek() {
+                Some(r) if r.start() <= id => fresh += 1,
+                Some(_) => {}
+                None => break,
+            }
+        }
+        fresh
+    }
+
+    /// How many IDs are fresh at all; an error only if that is 2^128, i.e. every `u128`.
+    fn fresh_count(&self) -> Result<u128> {
+        self.merged.iter().try_fold(0u128, |acc, r| {
+            r.count()
+                .and_then(|n| acc.checked_add(n))
+                .ok_or(anyhow!("fresh ID count overflows u128"))
+        })
+    }
+}
+
+/// The merged ranges, and for each queried ID the input ranges that contain it.
+#[derive(Debug, Clone)]
+struct FreshReport {
+    /// The range section in input order.
+    ranges: Vec<Inclusive>,
+    merged: Vec<Inclusive>,
+    /// Each ID with the 1-based positions in `ranges` of the ranges containing it.
+    provenance: Vec<(u128, Vec<usize>)>,
+}
+
+impl FreshReport {
+    fn new(ranges: Vec<Inclusive>, ids: &[u128]) -> Self {
+        let merged = FreshIndex::new(ranges.clone()).merged;
+        let mut by_start: Vec<usize> = (0..ranges.len()).collect();
+        by_start.sort_by_key(|&i| ranges[i]);
+        let provenance = ids
+            .iter()
+            .map(|&id| {
+                // Only ranges starting at or before the ID can hold it.
+                let candidates = by_start.partition_point(|&i| ranges[i].start() <= id);
+                let mut sources: Vec<usize> = by_start[..candidates]
+                    .iter()
+                    .filter(|&&i| ranges[i].contains(id))
+                    .map(|&i| i + 1)
+                    .collect();
+                sources.sort_unstable();
+                (id, sources)
+            })
+            .collect();
+        Self {
+            ranges,
+            merged,
+            provenance,
+        }
+    }
+
+    /// The merged ranges one per line, then `id: fresh (#n a-b, ...)` or `id: spoiled`.
+    fn to_text(&self) -> String {
+        let mut out = String::from("merged:\n");
+        for range in &self.merged {
+            out.push_str(&format!("{range}\n"));
+        }
+        out.push_str("ids:\n");
+        for (id, sources) in &self.provenance {
+            if sources.is_empty() {
+                out.push_str(&format!("{id}: spoiled\n"));
+                continue;
+            }
+            let sources: Vec<String> = sources
+                .iter()
+                .map(|&n| format!("#{n} {}", self.ranges[n - 1]))
+                .collect();
+            out.push_str(&format!("{id}: fresh ({})\n", sources.join(", ")));
+        }
+        out
+    }
+
+    /// `{"merged": [{"start", "end"}...], "ids": [{"id", "fresh", "ranges": [{"index",
+    /// "start", "end"}...]}...]}` on one line, `index` being the 1-based input position.
+    fn to_json(&self) -> String {
+        let range_json = |r: &Inclusive| format!("{{\"start\":{},\"end\":{}}}", r.start(), r.end());
+        let merged: Vec<String> = self.merged.iter().map(range_json).collect();
+        let ids: Vec<String> = self
+            .provenance
+            .iter()
+            .map(|(id, sources)| {
+                let sources: Vec<String> = sources
+                    .iter()
+                    .map(|&n| {
+                        let r = self.ranges[n - 1];
+                        format!(
+                            "{{\"index\":{n},\"start\":{},\"end\":{}}}",
+                            r.start(),
+                            r.end()
+                        )
+                    })
+                    .collect();
+                format!(
+                    "{{\"id\":{id},\"fresh\":{},\"ranges\":[{}]}}",
+                    !sources.is_empty(),
+                    sources.join(",")
+                )
+            })
+            .collect();
+        format!(
+            "{{\"merged\":[{}],\"ids\":[{}]}}",
+            merged.join(","),
+            ids.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adv_code_2025::rng::Rng;
+
+    fn range(start: u128, end: u128) -> Inclusive {
+        Inclusive::new(start, end).unwrap()
+    }
+
+    #[test]
+    fn test_fresh_index() {
+        let mut reader = BufReader::new(TEST.as_bytes());
+        let ranges = read_ranges(&mut reader).unwrap();
+        let ids = read_ids(reader).unwrap();
+        let index = FreshIndex::new(ranges);
+        assert_eq!(vec![range(3, 5), range(10, 20)], index.merged);
+        assert_eq!(
+            vec![5, 11, 17],
+            ids.iter()
+                .copied()
+                .filter(|&id| index.contains(id))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(3, index.count_fresh(&ids));
+        assert_eq!(14, index.fresh_count().unwrap());
+
+        assert!(read_ranges(&mut "5-3\n\n1\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_report() {
+        let mut reader = BufReader::new(TEST.as_bytes());
+        let ranges = read_ranges(&mut reader).unwrap();
+        let ids = read_ids(reader).unwrap();
+        let report = FreshReport::new(ranges, &ids);
+        assert_eq!(
+            "merged:\n3-5\n10-20\nids:\n1: spoiled\n5: fresh (#1 3-5)\n8: spoiled\n\
+             11: fresh (#2 10-14)\n17: fresh (#3 16-20, #4 12-18)\n32: spoiled\n",
+            report.to_text()
+        );
+        let json = report.to_json();
+        assert!(json.starts_with(
+            "{\"merged\":[{\"start\":3,\"end\":5},{\"start\":10,\"end\":20}],\"ids\":[\
+             {\"id\":1,\"fresh\":false,\"ranges\":[]},"
+        ));
+        assert!(json.contains(
+            "{\"id\":17,\"fresh\":true,\"ranges\":[{\"index\":3,\"start\":16,\"end\":20},\
+             {\"index\":4,\"start\":12,\"end\":18}]}"
+        ));
+        assert!(json.ends_with("]}"));
+    }
+
+    #[test]
+    fn test_limits() {
+        let top = FreshIndex::new(vec![
+            range(6, 8),
+            range(3, 5),
+            range(u128::MAX - 1, u128::MAX),
+            range(u128::MAX, u128::MAX),
+        ]);
+        assert_eq!(
+            vec![range(3, 8), range(u128::MAX - 1, u128::MAX)],
+            top.merged
+        );
+        assert!(top.contains(u128::MAX));
+        assert!(!top.contains(9));
+        assert_eq!(8, top.fresh_count().unwrap());
+        assert_eq!(1, top.count_fresh(&[u128::MAX, 0]));
+
+        let almost = FreshIndex::new(vec![range(2, u128::MAX), range(0, 0)]);
+        assert_eq!(u128::MAX, almost.fresh_count().unwrap());
+        assert!(FreshIndex::new(vec![range(0, u128::MAX)])
+            .fresh_count()
+            .is_err());
+    }
+
+    #[test]
+    fn test_against_naive() {
+        let mut rng = Rng::new(5);
+        let mut next = |bound: u64| rng.below(bound) as u128;
+        for _ in 0..50 {
+            let ranges: Vec<Inclusive> = (0..1 + next(40))
+                .map(|_| {
+                    let start = next(1_000);
+                    range(start, start + next(60))
+                })
+                .collect();
+            let ids: Vec<u128> = (0..200).map(|_| next(1_100)).collect();
+
+            let is_fresh = |id| ranges.iter().any(|r| r.contains(id));
+            let naive: Vec<bool> = ids.iter().map(|&id| is_fresh(id)).collect();
+            let all_fresh = (0..1_100).filter(|&id| is_fresh(id)).count() as u128;
+            let index = FreshIndex::new(ranges.clone());
+            assert_eq!(
+                naive,
+                ids.iter().map(|&id| index.contains(id)).collect::<Vec<_>>()
+            );
+            assert_eq!(
+                naive.iter().filter(|&&f| f).count(),
+                index.count_fresh(&ids)
+            );
+            assert_eq!(all_fresh, index.fresh_count().unwrap());
+            assert!(index.merged.windows(2).all(|w| !w[0].touches(&w[1])));
+        }
+    }
+}
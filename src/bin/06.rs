@@ -2,6 +2,7 @@ use adv_code_2025::*;
 use anyhow::*;
 use code_timing_macros::time_snippet;
 use const_format::concatcp;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::result::Result::Ok;
@@ -22,9 +23,11 @@ fn main() -> Result<()> {
     //region Part 1
     println!("=== Part 1 ===");
 
-    fn part1<R: BufRead>(reader: R) -> Result<usize> {
-        let lines: Vec<String> = reader.lines().map(|s| s.unwrap()).collect();
-        Ok(solve_part1(&lines))
+    fn part1<R: BufRead>(mut reader: R) -> Result<u128> {
+        let mut s = String::new();
+        reader.read_to_string(&mut s)?;
+
+        solve(&Worksheet::parse(&s)?, Reading::Rows, &Operators::default())
     }
 
     assert_eq!(4277556, part1(BufReader::new(TEST.as_bytes()))?);
@@ -37,11 +40,15 @@ fn main() -> Result<()> {
     //region Part 2
     println!("\n=== Part 2 ===");
 
-    fn part2<R: BufRead>(mut reader: R) -> Result<usize> {
+    fn part2<R: BufRead>(mut reader: R) -> Result<u128> {
         let mut s = String::new();
         reader.read_to_string(&mut s)?;
 
-        Ok(solve_part2(&s))
+        solve(
+            &Worksheet::parse(&s)?,
+            Reading::Columns,
+            &Operators::default(),
+        )
     }
 
     assert_eq!(3263827, part2(BufReader::new(TEST.as_bytes()))?);
@@ -54,58 +61,186 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn solve_part1(lines: &[String]) -> usize {
-    let mut vec = vec![];
-    for line in &lines[..lines.len() - 1] {
-        let v: Vec<u64> = line
-            .split_whitespace()
-            .map(|s| s.parse().unwrap())
-            .collect();
-        vec.push(v);
-    }
-    let mut ans = 0;
-    let symbols = lines.last().unwrap();
-    for (index, symbol) in symbols.split_whitespace().enumerate() {
-        match symbol {
-            "+" => {
-                ans += vec.iter().map(|r| r[index]).sum::<u64>() as usize;
-            }
-            "*" => {
-                ans += vec.iter().map(|r| r[index]).product::<u64>() as usize;
+/// One problem: the worksheet columns between two all-space columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Problem {
+    /// 0-based worksheet column where the problem starts.
+    column: usize,
+    /// The number rows cut to this problem's columns, so padding spaces are kept.
+    rows: Vec<Vec<u8>>,
+    op: String,
+}
+
+/// How the digits of a problem form numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reading {
+    /// One number per row, top to bottom (Part 1).
+    Rows,
+    /// One number per column, read top to bottom, columns right to left (Part 2).
+    Columns,
+}
+
+impl Problem {
+    fn numbers(&self, reading: Reading) -> Result<Vec<u128>> {
+        let width = self.rows.first().map_or(0, |row| row.len());
+        let runs: Vec<Vec<u8>> = match reading {
+            Reading::Rows => self.rows.clone(),
+            Reading::Columns => (0..width)
+                .rev()
+                .map(|c| self.rows.iter().map(|row| row[c]).collect())
+                .collect(),
+        };
+        let mut numbers = vec![];
+        for run in runs {
+            let digits = run.trim_ascii();
+            // A row can be blank when a problem has fewer operands than the worksheet has rows.
+            if digits.is_empty() {
+                continue;
             }
-            _ => panic!("Unknown symbol"),
+            ensure!(
+                digits.iter().all(u8::is_ascii_digit),
+                "expected digits, got {:?}",
+                String::from_utf8_lossy(digits)
+            );
+            numbers.push(std::str::from_utf8(digits)?.parse()?);
         }
+        Ok(numbers)
+    }
+
+    /// Folds the numbers left to right with the operator, e.g. `a - b - c` or `(a ^ b) ^ c`.
+    fn evaluate(&self, reading: Reading, operators: &Operators) -> Result<u128> {
+        let apply = operators
+            .get(&self.op)
+            .ok_or_else(|| anyhow!("unknown operator {:?}", self.op))?;
+        let numbers = self.numbers(reading)?;
+        let (&first, rest) = numbers.split_first().ok_or(anyhow!("no numbers"))?;
+        rest.iter().try_fold(first, |acc, &n| apply(acc, n))
+    }
+}
+
+/// A binary operator on `u128` that fails instead of overflowing or dividing by zero.
+type Operator = fn(u128, u128) -> Result<u128>;
+
+/// Operator symbols as written on the worksheet, mapped to how they are applied.
+struct Operators {
+    table: HashMap<String, Operator>,
+}
+
+impl Operators {
+    fn empty() -> Self {
+        Self {
+            table: HashMap::new(),
+        }
+    }
+
+    /// Adds `symbol`, replacing any operator already registered under it.
+    fn with(mut self, symbol: &str, operator: Operator) -> Self {
+        self.table.insert(symbol.to_string(), operator);
+        self
+    }
+
+    fn get(&self, symbol: &str) -> Option<Operator> {
+        self.table.get(symbol).copied()
+    }
+}
+
+impl Default for Operators {
+    /// `+ - * / % ^ max min`.
+    fn default() -> Self {
+        Self::empty()
+            .with("+", |a, b| {
+                a.checked_add(b).ok_or(anyhow!("{a} + {b} overflows"))
+            })
+            .with("-", |a, b| {
+                a.checked_sub(b).ok_or(anyhow!("{a} - {b} underflows"))
+            })
+            .with("*", |a, b| {
+                a.checked_mul(b).ok_or(anyhow!("{a} * {b} overflows"))
+            })
+            .with("/", |a, b| a.checked_div(b).ok_or(anyhow!("{a} / 0")))
+            .with("%", |a, b| a.checked_rem(b).ok_or(anyhow!("{a} % 0")))
+            .with("^", |a, b| {
+                u32::try_from(b)
+                    .ok()
+                    .and_then(|b| a.checked_pow(b))
+                    .ok_or(anyhow!("{a} ^ {b} overflows"))
+            })
+            .with("max", |a, b| Ok(a.max(b)))
+            .with("min", |a, b| Ok(a.min(b)))
     }
-    ans
 }
 
-fn solve_part2(data: &str) -> usize {
-    let mut column_data = vec![];
-    let mut ans = 0;
-    for line in data.lines() {
-        for (i, c) in line.chars().enumerate() {
-            if column_data.len() == i {
-                // init
-                column_data.push(0);
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Worksheet {
+    problems: Vec<Problem>,
+}
+
+impl Worksheet {
+    /// Splits the worksheet at every column that is blank in all rows, including the operator
+    /// row. Short lines are padded with spaces, so trailing whitespace does not matter.
+    fn parse(data: &str) -> Result<Self> {
+        let mut lines: Vec<&[u8]> = data
+            .lines()
+            .map(|line| line.trim_end_matches('\r').as_bytes())
+            .collect();
+        while lines
+            .last()
+            .is_some_and(|line| line.trim_ascii().is_empty())
+        {
+            lines.pop();
+        }
+        let Some((ops, rows)) = lines.split_last() else {
+            bail!("empty worksheet");
+        };
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let cell = |line: &[u8], c: usize| line.get(c).copied().unwrap_or(b' ');
+
+        let mut problems = vec![];
+        let mut c = 0;
+        while c < width {
+            if lines.iter().all(|line| cell(line, c) == b' ') {
+                c += 1;
+                continue;
             }
-            if let Some(d) = c.to_digit(10) {
-                // sum d{i} * 10^(len-1-i)
-                column_data[i] *= 10;
-                column_data[i] += d as usize;
-            } else if c == '+' {
-                ans += column_data[i..]
-                    .iter()
-                    .take_while(|&&x| x != 0)
-                    .sum::<usize>();
-            } else if c == '*' {
-                ans += column_data[i..]
-                    .iter()
-                    .take_while(|&&x| x != 0)
-                    .product::<usize>();
+            let start = c;
+            while c < width && lines.iter().any(|line| cell(line, c) != b' ') {
+                c += 1;
             }
+
+            let block_ops: Vec<u8> = (start..c).map(|c| cell(ops, c)).collect();
+            let block_ops = String::from_utf8_lossy(&block_ops);
+            let [op] = block_ops.split_whitespace().collect::<Vec<_>>()[..] else {
+                bail!(
+                    "columns {}-{}: expected one operator, found {:?}",
+                    start + 1,
+                    c,
+                    block_ops.trim()
+                );
+            };
+            problems.push(Problem {
+                column: start,
+                rows: rows
+                    .iter()
+                    .map(|line| (start..c).map(|c| cell(line, c)).collect())
+                    .collect(),
+                op: op.to_string(),
+            });
         }
+        Ok(Self { problems })
+    }
+}
+
+fn solve(worksheet: &Worksheet, reading: Reading, operators: &Operators) -> Result<u128> {
+    let mut ans: u128 = 0;
+    for (i, problem) in worksheet.problems.iter().enumerate() {
+        let value = problem
+            .evaluate(reading, operators)
+            .with_context(|| format!("problem {} (column {})", i + 1, problem.column + 1))?;
+        ans = ans
+            .checked_add(value)
+            .ok_or(anyhow!("grand total overflows at problem {}", i + 1))?;
     }
-    ans
+    Ok(ans)
 }
 
 #[cfg(test)]
@@ -121,12 +256,86 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let lines = TEST.lines().map(|s| s.to_string()).collect::<Vec<String>>();
-        assert_eq!(4277556, solve_part1(&lines));
+        let worksheet = Worksheet::parse(TEST).unwrap();
+        assert_eq!(4, worksheet.problems.len());
+        assert_eq!(
+            4277556,
+            solve(&worksheet, Reading::Rows, &Operators::default()).unwrap()
+        );
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(3263827, solve_part2(TEST));
+        let worksheet = Worksheet::parse(TEST).unwrap();
+        assert_eq!(
+            vec![4, 431, 623],
+            worksheet.problems[3].numbers(Reading::Columns).unwrap()
+        );
+        assert_eq!(
+            3263827,
+            solve(&worksheet, Reading::Columns, &Operators::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_zeros() {
+        // A 0 column used to end the problem early: 5 * 0 came out as 5.
+        let worksheet = Worksheet::parse("50 10\n   20\n*  +\n").unwrap();
+        assert_eq!(
+            vec![0, 5],
+            worksheet.problems[0].numbers(Reading::Columns).unwrap()
+        );
+        assert_eq!(
+            12,
+            solve(&worksheet, Reading::Columns, &Operators::default()).unwrap()
+        );
+        assert_eq!(
+            50 + 30,
+            solve(&worksheet, Reading::Rows, &Operators::default()).unwrap()
+        );
+
+        let all_zero = Worksheet::parse("00 7\n00 8\n+  *").unwrap();
+        assert_eq!(
+            78,
+            solve(&all_zero, Reading::Columns, &Operators::default()).unwrap()
+        );
+        assert_eq!(
+            56,
+            solve(&all_zero, Reading::Rows, &Operators::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Worksheet::parse("").is_err());
+        assert!(Worksheet::parse("123 4\n+ * *\n").is_err());
+        assert!(Worksheet::parse("1 2\n  +\n").is_err());
+        let worksheet = Worksheet::parse("1x 2\n+  +\n").unwrap();
+        assert!(solve(&worksheet, Reading::Rows, &Operators::default()).is_err());
+    }
+
+    #[test]
+    fn test_operators() {
+        let ops = Operators::default();
+        let eval = |sheet: &str| solve(&Worksheet::parse(sheet).unwrap(), Reading::Rows, &ops);
+        assert_eq!(10 - 3 - 2, eval("10\n 3\n 2\n-").unwrap());
+        assert_eq!(100 / 7 / 2, eval("100\n  7\n  2\n/").unwrap());
+        assert_eq!(100 % 7, eval("100\n  7\n%").unwrap());
+        assert_eq!(2u128.pow(3).pow(2), eval("2\n3\n2\n^").unwrap());
+        assert_eq!(9 + 1, eval("  4   1\n  9   5\nmax min").unwrap());
+
+        let err = |sheet: &str| format!("{:#}", eval(sheet).unwrap_err());
+        assert_eq!("problem 2 (column 3): 1 / 0", err("1 1\n1 0\n+ /"));
+        assert_eq!("problem 1 (column 1): 3 - 5 underflows", err("3\n5\n-"));
+        assert!(err("2\n200\n^").ends_with("2 ^ 200 overflows"));
+        assert!(err("1\n1\n&").ends_with("unknown operator \"&\""));
+
+        // Products that overflowed u64 are fine in u128.
+        let big = "4294967296\n4294967296\n4294967296\n*";
+        assert_eq!(1 << 96, eval(big).unwrap());
+
+        let custom = Operators::empty().with("+", |a, b| Ok(a.max(b)));
+        let worksheet = Worksheet::parse("1\n5\n+").unwrap();
+        assert_eq!(5, solve(&worksheet, Reading::Rows, &custom).unwrap());
     }
 }
@@ -1,19 +1,19 @@
+use adv_code_2025::gf2::BitSet;
+use adv_code_2025::matrix::Matrix;
+use adv_code_2025::rational::Rational;
 use adv_code_2025::*;
 use anyhow::*;
 use code_timing_macros::time_snippet;
 use const_format::concatcp;
 use itertools::Itertools;
-use regex::Regex;
-use std::cell::{Cell, RefCell};
-use std::collections::hash_map::Entry;
-use std::collections::{HashMap, VecDeque};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::rc::Rc;
-use z3::ast::{Ast, Bool, Int};
-use z3::DeclKind::OR;
-use z3::SortKind::Array;
-use z3::{Config, Optimize, SatResult};
+#[cfg(feature = "z3")]
+use {
+    std::cell::Cell,
+    z3::ast::{Ast, Bool, Int},
+    z3::{Optimize, SatResult},
+};
 
 const DAY: &str = "10";
 const INPUT_FILE: &str = concatcp!("input/", DAY, ".txt");
@@ -39,8 +39,8 @@ fn main() -> Result<()> {
         let mut ans = 0;
 
         for line in lines {
-            let l = parse_line(&line);
-            ans += solve_min_xor_elements(l.1, l.0)?.len();
+            let (target, buttons) = parse_line(&line)?;
+            ans += solve_min_xor_elements(&buttons, &target)?.len();
         }
 
         Ok(ans)
@@ -62,7 +62,9 @@ fn main() -> Result<()> {
             .map_while(Result::ok)
             .filter(|line| !line.trim().is_empty())
             .join("\n");
-        let ans = solve(&lines);
+        let ans = solve(&lines)?;
+        #[cfg(feature = "z3")]
+        ensure!(ans == solve_z3(&lines), "native solver disagrees with z3");
         Ok(ans as usize)
     }
 
@@ -76,95 +78,106 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn parse_line(line: &str) -> (u64, Vec<u64>) {
+fn parse_line(line: &str) -> Result<(BitSet, Vec<BitSet>)> {
     // [.##.]
-    let target: u64 = line
+    let bits = line
         .find('[')
         .and_then(|start| line.find(']').map(|end| &line[start + 1..end]))
-        .map(|bits| {
-            bits.chars()
-                .enumerate()
-                .map(|(i, c)| if c == '#' { 2u64.pow(i as u32) } else { 0 })
-                .sum()
-        })
-        .unwrap();
+        .ok_or(anyhow!("missing [lights]: {line}"))?;
+    let width = bits.len();
+    let target = BitSet::from_indices(
+        width,
+        bits.chars()
+            .enumerate()
+            .filter_map(|(i, c)| (c == '#').then_some(i)),
+    );
 
     // (3) (1,3) (2) (2,3) (0,2) (0,1)
-    let nums: Vec<u64> = line
-        .split(' ')
-        .filter_map(|part| {
-            if part.starts_with('(') && part.ends_with(')') {
-                let nums_str = &part[1..part.len() - 1];
-                let num: u64 = nums_str
-                    .split(',')
-                    .filter_map(|s| s.parse::<u64>().ok())
-                    .fold(0, |acc, n| acc | 2u64.pow(n as u32));
-                Some(num)
-            } else {
-                None
-            }
-        })
-        .collect();
+    let mut nums = vec![];
+    for part in line.split(' ') {
+        let Some(nums_str) = part.strip_prefix('(').and_then(|p| p.strip_suffix(')')) else {
+            continue;
+        };
+        let indices: Vec<usize> = nums_str.split(',').filter_map(|s| s.parse().ok()).collect();
+        if let Some(&i) = indices.iter().find(|&&i| i >= width) {
+            bail!("button {part} toggles light {i}, but there are only {width}");
+        }
+        nums.push(BitSet::from_indices(width, indices));
+    }
 
-    (target, nums)
+    Ok((target, nums))
 }
 
+#[cfg(feature = "z3")]
 thread_local! {
     static GET_ALL: Cell<bool> =  const { Cell::new(false) };
 }
 
-fn solve_min_xor_elements(nums: Vec<u64>, target: u64) -> Result<Vec<u64>> {
-    // 队列存储: (当前的异或值, 用了哪些原始数字)
-    let mut queue: VecDeque<(u64, Vec<u64>)> = VecDeque::new();
-
-    // 记录访问过的异或值，避免重复计算死循环
-    // Key: 异或值, Value: 达到这个值所需的最少数量
-    let mut visited: HashMap<u64, usize> = HashMap::new();
+/// Indices of the fewest buttons whose toggles XOR together to `target`.
+fn solve_min_xor_elements(buttons: &[BitSet], target: &BitSet) -> Result<Vec<usize>> {
+    let solution = gf2::solve(buttons, target).ok_or(anyhow!("无法组合出目标值"))?;
+    Ok(solution.min_weight().ones().collect())
+}
 
-    // 初始化：放入单个数字
-    for &num in &nums {
-        if num == target {
-            return Ok(vec![num]);
+// (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
+// Each button is a Vec<usize> of the counters it affects
+fn parse_joltage(line: &str) -> Result<(Vec<Vec<usize>>, Vec<i64>)> {
+    let mut buttons = Vec::new();
+    let mut targets = None;
+    for part in line.split_whitespace() {
+        if let Some(nums) = part.strip_prefix('(').and_then(|p| p.strip_suffix(')')) {
+            buttons.push(nums.split(',').map(str::parse).collect::<Result<_, _>>()?);
+        } else if let Some(nums) = part.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+            targets = Some(nums.split(',').map(str::parse).collect::<Result<_, _>>()?);
         }
-        if let Entry::Vacant(e) = visited.entry(num) {
-            e.insert(1);
-            queue.push_back((num, vec![num]));
+    }
+    Ok((buttons, targets.ok_or(anyhow!("missing joltages: {line}"))?))
+}
+
+/// Fewest total presses that bring every counter exactly to its joltage.
+fn min_presses(buttons: &[Vec<usize>], targets: &[i64]) -> Option<i64> {
+    let mut a = Matrix::zeros(targets.len(), buttons.len());
+    for (j, button) in buttons.iter().enumerate() {
+        for &r in button {
+            a[(r, j)] = Rational::ONE;
         }
     }
+    let b: Vec<Rational> = targets.iter().map(|&t| t.into()).collect();
 
-    // BFS 开始
-    while let Some((curr_val, mut path)) = queue.pop_front() {
-        // 尝试异或数组中的每一个原始数字
-        for &num in &nums {
-            let next_val = curr_val ^ num;
+    // Presses only ever add, so no button can be pressed more than its smallest counter allows.
+    let upper: Vec<i128> = buttons
+        .iter()
+        .map(|button| {
+            button
+                .iter()
+                .map(|&r| targets[r] as i128)
+                .min()
+                .unwrap_or(0)
+        })
+        .collect();
 
-            // 如果我们找到了目标
-            if next_val == target {
-                path.push(num);
-                return Ok(path);
-            }
+    let presses = ilp::minimize_sum(&a, &b, &upper)?;
+    Some(presses.iter().sum::<i128>() as i64)
+}
 
-            // 只有当我们发现了新的异或值，或者找到了更短路径到达该值时才入队
-            // (注：BFS本身保证了先到的一定是最短或等长，所以只需通过 visited 判重)
-            if let Entry::Vacant(e) = visited.entry(next_val) {
-                e.insert(path.len() + 1);
-                let mut next_path = path.clone();
-                next_path.push(num);
-                queue.push_back((next_val, next_path));
-            }
+fn solve(input_data: &str) -> Result<i64> {
+    let mut total_presses = 0;
+    for line in input_data.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
         }
+        let (buttons, targets) = parse_joltage(line)?;
+        total_presses +=
+            min_presses(&buttons, &targets).ok_or(anyhow!("No solution found for line: {line}"))?;
     }
-
-    Err(anyhow!("无法组合出目标值"))
+    Ok(total_presses)
 }
 
-fn solve(input_data: &str) -> i64 {
+#[cfg(feature = "z3")]
+fn solve_z3(input_data: &str) -> i64 {
     let mut total_presses = 0;
 
-    // Regex to capture groups inside () and the target group inside {}
-    let button_re = Regex::new(r"\(([\d,]+)\)").unwrap();
-    let target_re = Regex::new(r"\{([\d,]+)\}").unwrap();
-
     let print_all_solutions: bool = GET_ALL.get();
 
     for line in input_data.lines() {
@@ -174,22 +187,9 @@ fn solve(input_data: &str) -> i64 {
         }
 
         // --- 1. Parsing ---
-        // Extract targets
-        let target_caps = target_re.captures(line).expect("Failed to parse targets");
-        let targets: Vec<i64> = target_caps[1]
-            .split(',')
-            .map(|s| s.parse().unwrap())
-            .collect();
+        let (buttons, targets) = parse_joltage(line).expect("Failed to parse targets");
         let num_counters = targets.len();
 
-        // Extract buttons
-        // Each button is a Vec<usize> of the counters it affects
-        let mut buttons: Vec<Vec<usize>> = Vec::new();
-        for cap in button_re.captures_iter(line) {
-            let indices: Vec<usize> = cap[1].split(',').map(|s| s.parse().unwrap()).collect();
-            buttons.push(indices);
-        }
-
         // --- 2. Z3 Modeling ---
         let opt = Optimize::new();
 
@@ -274,24 +274,52 @@ fn solve(input_data: &str) -> i64 {
 
 #[cfg(test)]
 mod tests {
-    use crate::{solve, solve_min_xor_elements, GET_ALL, TEST};
+    use crate::{parse_line, solve, solve_min_xor_elements, TEST};
+    use adv_code_2025::gf2::BitSet;
 
+    #[cfg(feature = "z3")]
     #[test]
     fn test_part2_all_solutions() {
-        GET_ALL.set(true);
-        solve(TEST);
+        crate::GET_ALL.set(true);
+        crate::solve_z3(TEST);
+    }
+
+    #[test]
+    fn test_part2_native() {
+        assert_eq!(33, solve(TEST).unwrap());
     }
 
     #[test]
     fn test_solve_space() {
-        // [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
-        let a = solve_min_xor_elements(vec![8, 10, 4, 12, 5, 3], 6).unwrap();
-        println!("{a:?}");
-        // [...#.] (0,2,3,4) (2,3) (0,4) (0,1,2) (1,2,3,4) {7,5,12,7,2}
-        let a = solve_min_xor_elements(vec![29, 12, 17, 7, 30], 8).unwrap();
-        println!("{a:?}");
-        // [.###.#] (0,1,2,3,4) (0,3,4) (0,1,2,4,5) (1,2) {10,11,11,5,10,5}
-        let a = solve_min_xor_elements(vec![31, 25, 55, 5], 46).unwrap();
-        println!("{a:?}");
+        for (line, presses) in TEST.lines().zip([2, 3, 2]) {
+            let (target, buttons) = parse_line(line).unwrap();
+            let a = solve_min_xor_elements(&buttons, &target).unwrap();
+            assert_eq!(presses, a.len());
+        }
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        assert!(parse_line("[.#] (0) (1,2) {1,2}").is_err());
+        assert!(parse_line(".# (0) {1}").is_err());
+        assert!(parse_line("[.#] (0) (1) {1,2}").is_ok());
+    }
+
+    #[test]
+    fn test_solve_space_wide() {
+        // 200 lights chained by buttons (i, i+1) and closed by (199): lighting 0 takes every
+        // chain button, unless the single-light shortcut (0) is used instead.
+        let width = 200;
+        let mut buttons: Vec<BitSet> = (0..width - 1)
+            .map(|i| BitSet::from_indices(width, [i, i + 1]))
+            .collect();
+        buttons.push(BitSet::from_indices(width, [width - 1]));
+        buttons.push(BitSet::from_indices(width, [0]));
+        let target = BitSet::from_indices(width, [0]);
+        let a = solve_min_xor_elements(&buttons, &target).unwrap();
+        assert_eq!(vec![width], a);
+
+        let a = solve_min_xor_elements(&buttons[..width], &target).unwrap();
+        assert_eq!(width, a.len());
     }
 }
@@ -1,13 +1,12 @@
+use adv_code_2025::polyomino::{Puzzle, Shape};
+use adv_code_2025::terminal::{CLEAR, RESET};
 use anyhow::*;
 use std::cmp::Reverse;
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::HashMap;
 use std::io::{stdout, Write};
 use std::thread;
 use std::time::Duration;
 
-type Point = (i32, i32);
-type Shape = Vec<Point>;
-
 const TEST: &str = "\
 0:
 ###
@@ -42,57 +41,6 @@ const TEST: &str = "\
 12x5: 1 0 1 0 3 2
 ";
 
-fn normalize_shape(coords: HashSet<Point>) -> Shape {
-    if coords.is_empty() {
-        return Vec::new();
-    }
-    let min_x = coords.iter().map(|(x, _)| *x).min().unwrap();
-    let min_y = coords.iter().map(|(_, y)| *y).min().unwrap();
-
-    let mut normalized: Shape = coords
-        .into_iter()
-        .map(|(x, y)| (x - min_x, y - min_y))
-        .collect();
-    normalized.sort();
-    normalized
-}
-
-fn get_shape_dims(shape: &[Point]) -> (i32, i32) {
-    if shape.is_empty() {
-        return (0, 0);
-    }
-    let max_x = shape.iter().map(|(x, _)| *x).max().unwrap();
-    let max_y = shape.iter().map(|(_, y)| *y).max().unwrap();
-    (max_x + 1, max_y + 1)
-}
-
-fn rotate90(shape: &Shape) -> HashSet<Point> {
-    shape.iter().map(|(x, y)| (-*y, *x)).collect()
-}
-
-fn flip_h(shape: &Shape) -> HashSet<Point> {
-    shape.iter().map(|(x, y)| (-x, *y)).collect()
-}
-
-fn get_all_orientations(initial_shape: &HashSet<Point>) -> Vec<Shape> {
-    let mut orientations = BTreeSet::new();
-
-    let mut current_set = initial_shape.clone();
-
-    for _ in 0..4 {
-        let current_normalized = normalize_shape(current_set.clone());
-        orientations.insert(current_normalized.clone());
-
-        let flipped_set = flip_h(&current_normalized);
-        let flipped_normalized = normalize_shape(flipped_set);
-        orientations.insert(flipped_normalized);
-
-        current_set = rotate90(&current_normalized);
-    }
-
-    orientations.into_iter().collect()
-}
-
 // Colors for different shapes (ANSI escape codes)
 const COLORS: &[&str] = &[
     "\x1b[31m", // Red
@@ -107,10 +55,8 @@ const COLORS: &[&str] = &[
     "\x1b[94m", // Bright Blue
 ];
 
-const RESET: &str = "\x1b[0m";
-
 fn render_shape_to_lines(shape: &Shape, id: usize) -> Vec<String> {
-    let (w, h) = get_shape_dims(shape);
+    let (w, h) = shape.dims();
     let color = COLORS[id % COLORS.len()];
 
     // Easier way: build strings row by row
@@ -118,7 +64,7 @@ fn render_shape_to_lines(shape: &Shape, id: usize) -> Vec<String> {
     for y in 0..h {
         let mut line = String::new();
         for x in 0..w {
-            let is_set = shape.contains(&(x, y));
+            let is_set = shape.contains((x, y));
             if is_set {
                 line.push_str(&format!("{}██{}", color, RESET));
             } else {
@@ -233,8 +179,7 @@ fn generate_legend(
 }
 
 fn draw_grid(grid: &[Vec<Option<usize>>], legend_lines: &[String], steps: usize) {
-    // Clear screen and move cursor to top-left
-    print!("\x1b[2J\x1b[1;1H");
+    print!("{CLEAR}");
 
     let mut output_lines = Vec::new();
 
@@ -314,10 +259,6 @@ fn draw_grid(grid: &[Vec<Option<usize>>], legend_lines: &[String], steps: usize)
     stdout().flush().unwrap();
 }
 
-fn get_piece_area(shape: &Shape) -> usize {
-    shape.len()
-}
-
 fn has_unfillable_hole(
     grid: &[Vec<Option<usize>>],
     min_piece_area: usize,
@@ -385,7 +326,7 @@ fn solve_region_visualized(
         if count > 0 {
             if let Some(shapes) = shapes_orientations.get(&id) {
                 if let Some(first) = shapes.first() {
-                    let area = get_piece_area(first);
+                    let area = first.area();
                     if area < min_piece_area {
                         min_piece_area = area;
                     }
@@ -405,7 +346,7 @@ fn solve_region_visualized(
     pieces.sort_by_key(|&id| {
         let len = shapes_orientations
             .get(&id)
-            .map(|s| s[0].len())
+            .map(|s| s[0].area())
             .unwrap_or(0);
         Reverse(len)
     });
@@ -413,10 +354,11 @@ fn solve_region_visualized(
     let mut grid = vec![vec![None; width]; height];
     let mut steps = 0;
 
+    #[allow(clippy::too_many_arguments)]
     fn backtrack(
         piece_idx: usize,
         pieces: &[usize],
-        grid: &mut Vec<Vec<Option<usize>>>,
+        grid: &mut [Vec<Option<usize>>],
         shapes_orientations: &HashMap<usize, Vec<Shape>>,
         width: usize,
         height: usize,
@@ -444,7 +386,7 @@ fn solve_region_visualized(
         };
 
         for shape in orientations {
-            let (sw, sh) = get_shape_dims(shape);
+            let (sw, sh) = shape.dims();
             if sw as usize > width || sh as usize > height {
                 continue;
             }
@@ -456,7 +398,7 @@ fn solve_region_visualized(
                 for px in 0..=max_x {
                     let mut fits = true;
                     // Check collision
-                    for &(sx, sy) in shape {
+                    for &(sx, sy) in shape.cells() {
                         if grid[py + sy as usize][px + sx as usize].is_some() {
                             fits = false;
                             break;
@@ -465,7 +407,7 @@ fn solve_region_visualized(
 
                     if fits {
                         // Place piece
-                        for &(sx, sy) in shape {
+                        for &(sx, sy) in shape.cells() {
                             grid[py + sy as usize][px + sx as usize] = Some(shape_id);
                         }
 
@@ -492,7 +434,7 @@ fn solve_region_visualized(
                         }
 
                         // Backtrack (Remove piece)
-                        for &(sx, sy) in shape {
+                        for &(sx, sy) in shape.cells() {
                             grid[py + sy as usize][px + sx as usize] = None;
                         }
                     }
@@ -518,79 +460,20 @@ fn solve_region_visualized(
 }
 
 fn main() -> Result<()> {
-    let lines: Vec<&str> = TEST.lines().collect();
-    let mut i = 0;
-
-    let mut shapes = HashMap::new();
-    let mut regions = Vec::new();
-
-    while i < lines.len() {
-        let line = lines[i].trim();
-        if line.is_empty() {
-            i += 1;
-            continue;
-        }
-
-        if line.contains(':') && !line.contains('x') {
-            // Shape definition
-            let id_str = line.trim_end_matches(':');
-            let shape_id: usize = id_str.parse()?;
-            i += 1;
-
-            let mut coords = HashSet::new();
-            let mut y = 0;
-            while i < lines.len() {
-                let sl = lines[i];
-                if sl.trim().is_empty() {
-                    break;
-                }
-                let t = sl.trim();
-                if let Some(c) = t.chars().next() {
-                    if c.is_ascii_digit() && t.contains(':') {
-                        break;
-                    }
-                }
-
-                for (x, ch) in sl.chars().enumerate() {
-                    if ch == '#' {
-                        coords.insert((x as i32, y));
-                    }
-                }
-                y += 1;
-                i += 1;
-            }
-            shapes.insert(shape_id, normalize_shape(coords));
-        } else if line.contains(':') && line.contains('x') {
-            // Region definition
-            let parts: Vec<&str> = line.split(':').collect();
-            let dims: Vec<&str> = parts[0].trim().split('x').collect();
-            let w: usize = dims[0].parse()?;
-            let h: usize = dims[1].parse()?;
-
-            let counts: Vec<usize> = parts[1]
-                .trim()
-                .split_whitespace()
-                .map(|s| s.parse().unwrap())
-                .collect();
-            regions.push((w, h, counts));
-            i += 1;
-        } else {
-            i += 1;
-        }
-    }
-
-    let mut shapes_orientations = HashMap::new();
-    for (id, shape) in shapes {
-        let set_shape: HashSet<Point> = shape.into_iter().collect();
-        shapes_orientations.insert(id, get_all_orientations(&set_shape));
-    }
+    let puzzle = Puzzle::parse(TEST)?;
+    let shapes_orientations = puzzle.orientations();
 
     println!("Start Visualization...");
     thread::sleep(Duration::from_secs(1));
 
     // Run only for the second region (12x5) which has a known solution
-    if let Some((w, h, pieces)) = regions.get(0) {
-        if solve_region_visualized(*w, *h, &shapes_orientations, pieces) {
+    if let Some(region) = puzzle.regions.first() {
+        if solve_region_visualized(
+            region.width,
+            region.height,
+            &shapes_orientations,
+            &region.counts,
+        ) {
             println!("Solved!");
         } else {
             println!("No solution found.");
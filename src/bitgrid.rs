@@ -0,0 +1,122 @@
+use std::fmt;
+
+/// A 2D grid of bits, each row packed into `u64` words with column `c` at bit `c % 64` of word
+/// `c / 64`. Bits past the last column are always zero.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    rows: usize,
+    cols: usize,
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let stride = cols.div_ceil(64);
+        Self {
+            rows,
+            cols,
+            stride,
+            words: vec![0; rows * stride],
+        }
+    }
+
+    /// Non-zero cells become set bits; rows are assumed to be the same length.
+    pub fn from_rows(rows: &[Vec<u8>]) -> Self {
+        let cols = rows.first().map_or(0, |row| row.len());
+        let mut grid = Self::new(rows.len(), cols);
+        for (r, row) in rows.iter().enumerate() {
+            for (c, &cell) in row.iter().enumerate() {
+                grid.set(r, c, cell != 0);
+            }
+        }
+        grid
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<u8>> {
+        (0..self.rows)
+            .map(|r| (0..self.cols).map(|c| self.get(r, c) as u8).collect())
+            .collect()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Words per row.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        assert!(c < self.cols);
+        self.row(r)[c / 64] >> (c % 64) & 1 == 1
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, value: bool) {
+        assert!(c < self.cols);
+        let word = &mut self.row_mut(r)[c / 64];
+        if value {
+            *word |= 1 << (c % 64);
+        } else {
+            *word &= !(1 << (c % 64));
+        }
+    }
+
+    pub fn row(&self, r: usize) -> &[u64] {
+        &self.words[r * self.stride..(r + 1) * self.stride]
+    }
+
+    /// The caller must keep bits past the last column clear.
+    pub fn row_mut(&mut self, r: usize) -> &mut [u64] {
+        &mut self.words[r * self.stride..(r + 1) * self.stride]
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Heap bytes used by the cells.
+    pub fn memory(&self) -> usize {
+        self.words.len() * size_of::<u64>()
+    }
+}
+
+impl fmt::Display for BitGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                write!(f, "{}", if self.get(r, c) { '@' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitgrid() {
+        let rows = vec![vec![1, 0, 1], vec![0, 1, 0]];
+        let mut grid = BitGrid::from_rows(&rows);
+        assert_eq!((2, 3, 1), (grid.rows(), grid.cols(), grid.stride()));
+        assert_eq!(rows, grid.to_rows());
+        assert_eq!("@.@\n.@.\n", grid.to_string());
+        grid.set(1, 1, false);
+        assert_eq!(2, grid.count_ones());
+
+        let mut wide = BitGrid::new(3, 130);
+        wide.set(2, 129, true);
+        wide.set(0, 64, true);
+        assert_eq!(3, wide.stride());
+        assert_eq!(&[0, 0, 0b10], wide.row(2));
+        assert_eq!(&[0, 1, 0], wide.row(0));
+    }
+}
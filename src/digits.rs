@@ -0,0 +1,213 @@
+/// Decimal digit and small number-theory helpers shared by the digit-oriented puzzles.
+///
+/// The plain methods panic on overflow like the std arithmetic they wrap; the `checked_` ones
+/// return `None` instead. The `_radix` variants take any base in `2..=36` and panic outside it.
+pub trait Digits: Copy + Sized {
+    /// Number of decimal digits, counting `0` as one digit.
+    fn digit_count(self) -> u32 {
+        self.digit_count_radix(10)
+    }
+
+    fn digit_count_radix(self, radix: u32) -> u32;
+
+    /// Lowercase digits in `radix`, the inverse of `from_str_radix`.
+    fn to_radix_string(self, radix: u32) -> String;
+
+    /// Decimal digits, most significant first.
+    fn digits(self) -> DigitIter<Self>;
+
+    fn checked_pow10(exp: u32) -> Option<Self> {
+        Self::checked_pow_radix(10, exp)
+    }
+
+    fn checked_pow_radix(radix: u32, exp: u32) -> Option<Self>;
+
+    fn pow_radix(radix: u32, exp: u32) -> Self {
+        Self::checked_pow_radix(radix, exp).expect("radix^exp overflowed")
+    }
+
+    fn pow10(exp: u32) -> Self {
+        Self::checked_pow10(exp).expect("10^exp overflowed")
+    }
+
+    /// `self * 10 + digit`, i.e. appends one digit on the right.
+    fn checked_push_digit(self, digit: u8) -> Option<Self>;
+
+    fn push_digit(self, digit: u8) -> Self {
+        self.checked_push_digit(digit)
+            .expect("push_digit overflowed")
+    }
+
+    /// The digits of `self` followed by the digits of `rhs`, e.g. `12.concat(34) == 1234`.
+    fn checked_concat(self, rhs: Self) -> Option<Self>;
+
+    fn concat(self, rhs: Self) -> Self {
+        self.checked_concat(rhs).expect("concat overflowed")
+    }
+
+    /// `Σ 10^(i·seed_len)` for `i < repeats`: multiplying a `seed_len`-digit seed by this writes
+    /// it out `repeats` times, e.g. `repunit(2, 3) == 10101` and `12 * 10101 == 121212`.
+    fn checked_repunit(seed_len: u32, repeats: u32) -> Option<Self> {
+        Self::checked_repunit_radix(10, seed_len, repeats)
+    }
+
+    fn checked_repunit_radix(radix: u32, seed_len: u32, repeats: u32) -> Option<Self>;
+
+    fn repunit(seed_len: u32, repeats: u32) -> Self {
+        Self::checked_repunit(seed_len, repeats).expect("repunit overflowed")
+    }
+
+    fn repunit_radix(radix: u32, seed_len: u32, repeats: u32) -> Self {
+        Self::checked_repunit_radix(radix, seed_len, repeats).expect("repunit overflowed")
+    }
+
+    /// All positive divisors in increasing order; empty for zero.
+    fn divisors(self) -> Vec<Self>;
+}
+
+/// Iterator over the decimal digits of a number, see [`Digits::digits`].
+#[derive(Debug, Clone)]
+pub struct DigitIter<T> {
+    rest: T,
+    place: T,
+}
+
+macro_rules! impl_digits {
+    ($($t:ty),*) => {$(
+        impl Digits for $t {
+            fn digit_count_radix(self, radix: u32) -> u32 {
+                assert!((2..=36).contains(&radix), "radix {radix} out of range");
+                self.checked_ilog(radix as $t).map_or(1, |e| e + 1)
+            }
+
+            fn to_radix_string(self, radix: u32) -> String {
+                let mut rest = self;
+                let mut digits = Vec::with_capacity(self.digit_count_radix(radix) as usize);
+                loop {
+                    let digit = (rest % radix as $t) as u32;
+                    digits.push(char::from_digit(digit, radix).unwrap());
+                    rest /= radix as $t;
+                    if rest == 0 {
+                        break;
+                    }
+                }
+                digits.into_iter().rev().collect()
+            }
+
+            fn digits(self) -> DigitIter<Self> {
+                DigitIter {
+                    rest: self,
+                    place: Self::pow10(self.digit_count() - 1),
+                }
+            }
+
+            fn checked_pow_radix(radix: u32, exp: u32) -> Option<Self> {
+                (radix as $t).checked_pow(exp)
+            }
+
+            fn checked_push_digit(self, digit: u8) -> Option<Self> {
+                debug_assert!(digit < 10);
+                self.checked_mul(10)?.checked_add(digit as $t)
+            }
+
+            fn checked_concat(self, rhs: Self) -> Option<Self> {
+                self.checked_mul(Self::checked_pow10(rhs.digit_count())?)?
+                    .checked_add(rhs)
+            }
+
+            fn checked_repunit_radix(radix: u32, seed_len: u32, repeats: u32) -> Option<Self> {
+                let step = Self::checked_pow_radix(radix, seed_len)?;
+                let mut acc: $t = 0;
+                for _ in 0..repeats {
+                    acc = acc.checked_mul(step)?.checked_add(1)?;
+                }
+                Some(acc)
+            }
+
+            fn divisors(self) -> Vec<Self> {
+                let mut small = Vec::new();
+                let mut large = Vec::new();
+                let mut d: $t = 1;
+                while d <= self / d {
+                    if self % d == 0 {
+                        small.push(d);
+                        if d != self / d {
+                            large.push(self / d);
+                        }
+                    }
+                    d += 1;
+                }
+                small.extend(large.into_iter().rev());
+                small
+            }
+        }
+
+        impl Iterator for DigitIter<$t> {
+            type Item = u8;
+
+            fn next(&mut self) -> Option<u8> {
+                if self.place == 0 {
+                    return None;
+                }
+                let digit = (self.rest / self.place) as u8;
+                self.rest %= self.place;
+                self.place /= 10;
+                Some(digit)
+            }
+        }
+    )*};
+}
+
+impl_digits!(u32, u64, u128, usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(1, 0u64.digit_count());
+        assert_eq!(2, 32u64.digit_count());
+        assert_eq!(39, u128::MAX.digit_count());
+        assert_eq!(vec![1, 0, 2], 102u64.digits().collect::<Vec<_>>());
+        assert_eq!(vec![0], 0u32.digits().collect::<Vec<_>>());
+        assert_eq!(1234, 12u64.concat(34));
+        assert_eq!(120, 12u64.concat(0));
+        assert_eq!(None, u64::MAX.checked_concat(1));
+        assert_eq!(987, [9, 8, 7].into_iter().fold(0usize, Digits::push_digit));
+    }
+
+    #[test]
+    fn test_repunit() {
+        assert_eq!(10101, u128::repunit(2, 3));
+        assert_eq!(111, u64::repunit(1, 3));
+        assert_eq!(0, u64::repunit(5, 0));
+        assert_eq!(Some(10_000_000_001), u64::checked_repunit(10, 2));
+        assert_eq!(None, u64::checked_repunit(10, 3));
+        assert!(u128::checked_repunit(10, 4).is_some());
+        assert!(u128::checked_repunit(10, 5).is_none());
+    }
+
+    #[test]
+    fn test_radix() {
+        assert_eq!(4, 0b1010u64.digit_count_radix(2));
+        assert_eq!(32, u128::MAX.digit_count_radix(16));
+        assert_eq!("ff", 255u32.to_radix_string(16));
+        assert_eq!("0", 0u64.to_radix_string(2));
+        assert_eq!(
+            Ok(12345),
+            u64::from_str_radix(&12345u64.to_radix_string(36), 36)
+        );
+        assert_eq!(0b10101, u64::repunit_radix(2, 2, 3));
+        assert_eq!(Some(0x1_0001), u32::checked_repunit_radix(16, 4, 2));
+        assert_eq!(None, u32::checked_repunit_radix(16, 4, 3));
+    }
+
+    #[test]
+    fn test_divisors() {
+        assert_eq!(vec![1, 2, 3, 4, 6, 12], 12u64.divisors());
+        assert_eq!(vec![1, 7], 7u128.divisors());
+        assert_eq!(vec![1], 1u32.divisors());
+        assert!(0u64.divisors().is_empty());
+    }
+}
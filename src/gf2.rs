@@ -0,0 +1,750 @@
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::fmt;
+
+const WORD: usize = u64::BITS as usize;
+
+/// A fixed-width row of bits, i.e. a vector over GF(2).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(WORD)],
+            len,
+        }
+    }
+
+    pub fn from_indices(len: usize, indices: impl IntoIterator<Item = usize>) -> Self {
+        let mut set = Self::new(len);
+        for i in indices {
+            set.set(i, true);
+        }
+        set
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "bit {i} out of range for width {}", self.len);
+        (self.words[i / WORD] >> (i % WORD)) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, value: bool) {
+        assert!(i < self.len, "bit {i} out of range for width {}", self.len);
+        let mask = 1 << (i % WORD);
+        if value {
+            self.words[i / WORD] |= mask;
+        } else {
+            self.words[i / WORD] &= !mask;
+        }
+    }
+
+    pub fn xor_with(&mut self, other: &BitSet) {
+        assert_eq!(self.len, other.len);
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+
+    pub fn or_with(&mut self, other: &BitSet) {
+        assert_eq!(self.len, other.len);
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &w)| {
+            let mut w = w;
+            std::iter::from_fn(move || {
+                (w != 0).then(|| {
+                    let bit = w.trailing_zeros() as usize;
+                    w &= w - 1;
+                    i * WORD + bit
+                })
+            })
+        })
+    }
+}
+
+impl fmt::Display for BitSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in 0..self.len {
+            write!(f, "{}", if self.get(i) { '1' } else { '0' })?;
+        }
+        Ok(())
+    }
+}
+
+/// All solutions of `A·x = b` over GF(2): `particular` XOR any subset of `nullspace`.
+#[derive(Debug, Clone)]
+pub struct Gf2Solution {
+    pub particular: BitSet,
+    pub nullspace: Vec<BitSet>,
+}
+
+/// Solves `A·x = target` where `columns[j]` is column `j` of `A`.
+///
+/// For the light puzzle each column is a button and each bit a light it toggles, so `x` picks the
+/// buttons to press. Returns `None` when the target is unreachable.
+pub fn solve(columns: &[BitSet], target: &BitSet) -> Option<Gf2Solution> {
+    let vars = columns.len();
+    let eqs = target.len();
+    assert!(
+        columns.iter().all(|c| c.len() == eqs),
+        "column width mismatch"
+    );
+
+    // Row `r` holds the coefficients of equation `r`, with the right-hand side in bit `vars`.
+    let mut rows: Vec<BitSet> = (0..eqs)
+        .map(|r| {
+            let mut row = BitSet::new(vars + 1);
+            for (j, col) in columns.iter().enumerate() {
+                if col.get(r) {
+                    row.set(j, true);
+                }
+            }
+            row.set(vars, target.get(r));
+            row
+        })
+        .collect();
+
+    let mut pivots = Vec::new();
+    let mut free = Vec::new();
+    for col in 0..vars {
+        let rank = pivots.len();
+        let Some(found) = (rank..eqs).find(|&r| rows[r].get(col)) else {
+            free.push(col);
+            continue;
+        };
+        rows.swap(rank, found);
+        let pivot_row = rows[rank].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r != rank && row.get(col) {
+                row.xor_with(&pivot_row);
+            }
+        }
+        pivots.push(col);
+    }
+
+    if rows[pivots.len()..].iter().any(|row| row.get(vars)) {
+        return None;
+    }
+
+    let mut particular = BitSet::new(vars);
+    for (r, &p) in pivots.iter().enumerate() {
+        particular.set(p, rows[r].get(vars));
+    }
+
+    let nullspace = free
+        .iter()
+        .map(|&f| {
+            let mut v = BitSet::new(vars);
+            v.set(f, true);
+            for (r, &p) in pivots.iter().enumerate() {
+                if rows[r].get(f) {
+                    v.set(p, true);
+                }
+            }
+            v
+        })
+        .collect();
+
+    Some(Gf2Solution {
+        particular,
+        nullspace,
+    })
+}
+
+/// Largest rank searched by syndrome, i.e. a table of `2^rank` entries.
+const MAX_SYNDROME_BITS: usize = 20;
+
+/// Largest rank searched by splitting syndromes, which are packed into a `u64`.
+const MAX_SPLIT_SYNDROME_BITS: usize = 64;
+
+/// Most right halves the split search keeps in memory, 16 bytes each.
+const MAX_SPLIT_TABLE: usize = 1 << 23;
+
+impl Gf2Solution {
+    /// The solution with the fewest set bits.
+    ///
+    /// When the rank is small next to the nullity, this searches syndromes: breadth-first over
+    /// all `2^rank` of them for a small rank, or meeting in the middle up to a rank of 64.
+    /// Otherwise it enumerates the solutions by their weight on several disjoint information
+    /// sets (Brouwer–Zimmermann), which grows with the answer rather than with `2^nullity`.
+    pub fn min_weight(&self) -> BitSet {
+        let nullity = self.nullspace.len();
+        let rank = self.particular.len() - nullity;
+        if nullity == 0 {
+            self.particular.clone()
+        } else if rank < nullity && rank <= MAX_SYNDROME_BITS {
+            self.min_weight_by_syndrome()
+        } else if rank < nullity && rank <= MAX_SPLIT_SYNDROME_BITS {
+            self.min_weight_by_split_syndrome()
+        } else {
+            self.min_weight_by_information_sets()
+        }
+    }
+
+    /// Shortest path from syndrome 0 to the particular solution's syndrome, one button per edge.
+    fn min_weight_by_syndrome(&self) -> BitSet {
+        let vars = self.particular.len();
+        let (syndromes, target) = self.syndromes();
+        let syndromes: Vec<usize> = syndromes.into_iter().map(|s| s as usize).collect();
+        let target = target as usize;
+        let rank = vars - self.nullspace.len();
+
+        // Columns with the same syndrome are interchangeable, so keep one of each.
+        let mut edges: Vec<usize> = (0..vars).filter(|&j| syndromes[j] != 0).collect();
+        edges.sort_by_key(|&j| syndromes[j]);
+        edges.dedup_by_key(|j| syndromes[*j]);
+
+        // `via[s]` is the column whose press first reached syndrome `s`.
+        let mut via = vec![usize::MAX; 1 << rank];
+        let mut queue = VecDeque::from([0]);
+        while let Some(s) = queue.pop_front() {
+            if s == target {
+                break;
+            }
+            for &j in &edges {
+                let next = s ^ syndromes[j];
+                if next != 0 && via[next] == usize::MAX {
+                    via[next] = j;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        // A shortest path never uses a column twice, since the two presses would cancel.
+        let mut best = BitSet::new(vars);
+        let mut s = target;
+        while s != 0 {
+            best.set(via[s], true);
+            s ^= syndromes[via[s]];
+        }
+        best
+    }
+
+    /// Each column's syndrome under the parity checks, the vectors orthogonal to the whole
+    /// nullspace, together with the particular solution's syndrome. There are `rank` checks,
+    /// so this needs `rank <= 64`.
+    fn syndromes(&self) -> (Vec<u64>, u64) {
+        let vars = self.particular.len();
+        let nullity = self.nullspace.len();
+        let generator: Vec<BitSet> = (0..vars)
+            .map(|j| {
+                BitSet::from_indices(nullity, (0..nullity).filter(|&i| self.nullspace[i].get(j)))
+            })
+            .collect();
+        let checks = solve(&generator, &BitSet::new(nullity))
+            .expect("the zero vector is always a solution")
+            .nullspace;
+        assert!(
+            checks.len() <= 64,
+            "{} parity checks do not fit a u64",
+            checks.len()
+        );
+        let syndromes: Vec<u64> = (0..vars)
+            .map(|j| {
+                (0..checks.len())
+                    .filter(|&r| checks[r].get(j))
+                    .fold(0, |s, r| s | 1 << r)
+            })
+            .collect();
+        let target = self.particular.ones().fold(0, |s, j| s ^ syndromes[j]);
+        (syndromes, target)
+    }
+
+    /// Meet in the middle: a solution of weight `w` splits into its first `⌈w/2⌉` columns and
+    /// its last `⌊w/2⌋`, whose syndromes XOR to the target. The right halves go into a table
+    /// keyed by syndrome, and each left half looks up the syndrome it is missing, so weight
+    /// `w` costs about `C(n, w/2)` instead of `C(n, w)` or `2^rank`. Past
+    /// [`MAX_SPLIT_TABLE`] right halves, the left halves take the extra columns instead.
+    fn min_weight_by_split_syndrome(&self) -> BitSet {
+        let vars = self.particular.len();
+        let (syndromes, target) = self.syndromes();
+
+        // Columns with the same syndrome are interchangeable, so keep one of each.
+        let mut edges: Vec<usize> = (0..vars).filter(|&j| syndromes[j] != 0).collect();
+        edges.sort_by_key(|&j| syndromes[j]);
+        edges.dedup_by_key(|j| syndromes[*j]);
+        let columns: Vec<u64> = edges.iter().map(|&j| syndromes[j]).collect();
+        let max_right = (0..=columns.len())
+            .take_while(|&k| binomial(columns.len(), k) <= MAX_SPLIT_TABLE as u128)
+            .last()
+            .unwrap_or(0);
+
+        let mut right_size = None;
+        let mut table = Vec::new();
+        let mut filter = SyndromeFilter::default();
+        for w in 0.. {
+            let right = (w / 2).min(max_right);
+            let left = w - right;
+            if right_size != Some(right) {
+                // Keep the latest first column per syndrome, so a left half ending before it
+                // can always be extended. The empty half starts after every column.
+                table.clear();
+                for_each_subset(&columns, right, &mut |picked, s| {
+                    table.push((s, picked.first().copied().unwrap_or(columns.len())));
+                    false
+                });
+                table.sort_unstable_by_key(|&(s, first)| (s, Reverse(first)));
+                table.dedup_by_key(|&mut (s, _)| s);
+                filter = SyndromeFilter::new(table.iter().map(|&(s, _)| s));
+                right_size = Some(right);
+            }
+
+            let mut found = None;
+            for_each_subset(&columns, left, &mut |picked, s| {
+                let missing = target ^ s;
+                if !filter.may_contain(missing) {
+                    return false;
+                }
+                let fits = table
+                    .binary_search_by_key(&missing, |&(s, _)| s)
+                    .is_ok_and(|i| picked.last().is_none_or(|&last| table[i].1 > last));
+                if fits {
+                    found = Some((picked.to_vec(), missing));
+                }
+                fits
+            });
+            let Some((mut picked, missing)) = found else {
+                continue;
+            };
+
+            let after = picked.last().map_or(0, |&last| last + 1);
+            for_each_subset(&columns[after..], right, &mut |rest, s| {
+                if s == missing {
+                    picked.extend(rest.iter().map(|&i| i + after));
+                }
+                s == missing
+            });
+            return BitSet::from_indices(vars, picked.into_iter().map(|i| edges[i]));
+        }
+        unreachable!("the particular solution has a finite weight")
+    }
+
+    /// Splits the columns into disjoint information sets: column sets on which the nullspace
+    /// has full rank, so a solution is fixed by its bits there. Visiting every solution with
+    /// at most `w` bits inside each of `m` sets leaves only solutions of weight at least
+    /// `m·(w + 1)`, which ends the search once that reaches the best weight found.
+    fn min_weight_by_information_sets(&self) -> BitSet {
+        let mut used = BitSet::new(self.particular.len());
+        let mut sets = Vec::new();
+        while let Some(set) = self.information_set(&mut used) {
+            sets.push(set);
+        }
+
+        let mut best = self.particular.clone();
+        let mut best_weight = best.count_ones();
+        let m = sets.len();
+        for w in 0.. {
+            for (i, set) in sets.iter().enumerate() {
+                // Inside the set a pick of `w` generators has exactly `w` bits.
+                if let Some(picked) = set.lightest(w, best_weight.saturating_sub(w)) {
+                    best = set.offset.clone();
+                    for j in picked {
+                        best.xor_with(&set.gens[j]);
+                    }
+                    best_weight = best.count_ones();
+                }
+                if m * w + i + 1 >= best_weight {
+                    return best;
+                }
+            }
+        }
+        unreachable!()
+    }
+
+    /// Picks an information set among the columns not yet `used` and marks it, or returns
+    /// `None` once the remaining columns have too little rank.
+    fn information_set(&self, used: &mut BitSet) -> Option<InformationSet> {
+        let k = self.nullspace.len();
+        let mut gens = self.nullspace.clone();
+        let mut pivots = Vec::with_capacity(k);
+        for col in 0..used.len() {
+            if pivots.len() == k {
+                break;
+            }
+            let rank = pivots.len();
+            if used.get(col) {
+                continue;
+            }
+            let Some(found) = (rank..k).find(|&r| gens[r].get(col)) else {
+                continue;
+            };
+            gens.swap(rank, found);
+            let pivot_row = gens[rank].clone();
+            for (r, row) in gens.iter_mut().enumerate() {
+                if r != rank && row.get(col) {
+                    row.xor_with(&pivot_row);
+                }
+            }
+            pivots.push(col);
+        }
+        if pivots.len() < k {
+            return None;
+        }
+
+        let mut offset = self.particular.clone();
+        for (gen, &col) in gens.iter().zip(&pivots) {
+            if offset.get(col) {
+                offset.xor_with(gen);
+            }
+        }
+        let inside = BitSet::from_indices(used.len(), pivots.iter().copied());
+        used.or_with(&inside);
+
+        let rest: Vec<usize> = (0..used.len()).filter(|&c| !inside.get(c)).collect();
+        let restrict = |v: &BitSet| {
+            BitSet::from_indices(rest.len(), (0..rest.len()).filter(|&i| v.get(rest[i]))).words
+        };
+        Some(InformationSet {
+            rest_offset: restrict(&offset),
+            rest_gens: gens.iter().flat_map(restrict).collect(),
+            offset,
+            gens,
+        })
+    }
+}
+
+/// The solutions as `offset` XOR a subset of `gens`, where the set's columns hold exactly the
+/// subset: `offset` is zero there and `gens` are the identity.
+struct InformationSet {
+    offset: BitSet,
+    gens: Vec<BitSet>,
+    /// `offset` and `gens` without the set's columns, as raw words; the generators are laid
+    /// out one after another, `rest_offset.len()` words each.
+    rest_offset: Vec<u64>,
+    rest_gens: Vec<u64>,
+}
+
+impl InformationSet {
+    /// The pick of exactly `w` generators that is lightest outside the set, if lighter than
+    /// `bound` there. The first pick is spread over threads.
+    fn lightest(&self, w: usize, bound: usize) -> Option<Vec<usize>> {
+        let k = self.gens.len();
+        let stride = self.rest_offset.len();
+        let search = |first: Option<usize>| {
+            let mut search = Search {
+                gens: &self.rest_gens,
+                current: self.rest_offset.clone(),
+                picked: Vec::with_capacity(w),
+                bound,
+                best: None,
+            };
+            match first {
+                Some(j) => {
+                    let gen = &self.rest_gens[j * stride..(j + 1) * stride];
+                    for (a, b) in search.current.iter_mut().zip(gen) {
+                        *a ^= b;
+                    }
+                    search.picked.push(j);
+                    search.visit(j + 1, w - 1);
+                }
+                None => search.visit(0, 0),
+            }
+            search.best.map(|picked| (search.bound, picked))
+        };
+
+        let found = if w == 0 {
+            search(None)
+        } else {
+            (0..(k + 1).saturating_sub(w))
+                .into_par_iter()
+                .filter_map(|j| search(Some(j)))
+                .min_by_key(|(weight, _)| *weight)
+        };
+        found.map(|(_, picked)| picked)
+    }
+}
+
+/// Enumerates picks of generators by DFS, keeping the lightest below `bound`. Works on raw
+/// words since this is the hot loop of [`Gf2Solution::min_weight`].
+struct Search<'a> {
+    /// Packed like [`InformationSet::rest_gens`].
+    gens: &'a [u64],
+    current: Vec<u64>,
+    picked: Vec<usize>,
+    bound: usize,
+    best: Option<Vec<usize>>,
+}
+
+impl Search<'_> {
+    /// Every way to XOR exactly `left` more of the generators from `start` on into `current`.
+    fn visit(&mut self, start: usize, left: usize) {
+        let stride = self.current.len();
+        let end = (self.gens.len() / stride + 1).saturating_sub(left);
+        let range = start * stride..end * stride;
+        match left {
+            0 => {
+                let weight = self.current.iter().map(|w| w.count_ones() as usize).sum();
+                if weight < self.bound {
+                    self.bound = weight;
+                    self.best = Some(self.picked.clone());
+                }
+            }
+            // The last pick only needs the weight of each candidate, not the candidate itself.
+            1 => {
+                'candidates: for (j, gen) in self.gens[range].chunks_exact(stride).enumerate() {
+                    // Nearly every candidate is already too heavy after the first word.
+                    let mut weight = 0;
+                    for (a, b) in self.current.iter().zip(gen) {
+                        weight += (a ^ b).count_ones() as usize;
+                        if weight >= self.bound {
+                            continue 'candidates;
+                        }
+                    }
+                    {
+                        self.bound = weight;
+                        let mut picked = self.picked.clone();
+                        picked.push(start + j);
+                        self.best = Some(picked);
+                    }
+                }
+            }
+            _ => {
+                for j in start..end {
+                    let gen = &self.gens[j * stride..(j + 1) * stride];
+                    for (a, b) in self.current.iter_mut().zip(gen) {
+                        *a ^= b;
+                    }
+                    self.picked.push(j);
+                    self.visit(j + 1, left - 1);
+                    self.picked.pop();
+                    for (a, b) in self.current.iter_mut().zip(gen) {
+                        *a ^= b;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `C(n, k)`, saturating at `u128::MAX`.
+fn binomial(n: usize, k: usize) -> u128 {
+    (0..k.min(n + 1) as u128).fold(1u128, |c, i| c.saturating_mul(n as u128 - i) / (i + 1))
+}
+
+/// Calls `visit` with every `size`-subset of `columns`, as increasing indices, and the XOR of
+/// its syndromes, until `visit` returns `true`. Returns whether it did.
+fn for_each_subset(
+    columns: &[u64],
+    size: usize,
+    visit: &mut impl FnMut(&[usize], u64) -> bool,
+) -> bool {
+    fn go(
+        columns: &[u64],
+        start: usize,
+        left: usize,
+        syndrome: u64,
+        picked: &mut Vec<usize>,
+        visit: &mut impl FnMut(&[usize], u64) -> bool,
+    ) -> bool {
+        if left == 0 {
+            return visit(picked, syndrome);
+        }
+        for j in start..(columns.len() + 1).saturating_sub(left) {
+            picked.push(j);
+            let stop = go(
+                columns,
+                j + 1,
+                left - 1,
+                syndrome ^ columns[j],
+                picked,
+                visit,
+            );
+            picked.pop();
+            if stop {
+                return true;
+            }
+        }
+        false
+    }
+    go(columns, 0, size, 0, &mut Vec::with_capacity(size), visit)
+}
+
+/// A bitmap over hashed syndromes, about 16 bits per key, so most lookups of a missing
+/// syndrome are rejected without touching the hash map.
+#[derive(Default)]
+struct SyndromeFilter {
+    bits: Vec<u64>,
+    shift: u32,
+}
+
+impl SyndromeFilter {
+    fn new(keys: impl ExactSizeIterator<Item = u64>) -> Self {
+        let size = (keys.len() * 16).next_power_of_two().max(WORD);
+        let mut filter = Self {
+            bits: vec![0; size / WORD],
+            shift: u64::BITS - size.trailing_zeros(),
+        };
+        for key in keys {
+            let i = filter.slot(key);
+            filter.bits[i / WORD] |= 1 << (i % WORD);
+        }
+        filter
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        (key.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> self.shift) as usize
+    }
+
+    fn may_contain(&self, key: u64) -> bool {
+        let i = self.slot(key);
+        (self.bits[i / WORD] >> (i % WORD)) & 1 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn lights(width: usize, on: &[usize]) -> BitSet {
+        BitSet::from_indices(width, on.iter().copied())
+    }
+
+    #[test]
+    fn test_bitset_wide() {
+        let mut a = lights(130, &[0, 64, 129]);
+        assert_eq!(vec![0, 64, 129], a.ones().collect::<Vec<_>>());
+        a.xor_with(&lights(130, &[64, 65]));
+        assert_eq!(vec![0, 65, 129], a.ones().collect::<Vec<_>>());
+        assert_eq!(3, a.count_ones());
+    }
+
+    #[test]
+    fn test_min_weight() {
+        // [.##.] (3) (1,3) (2) (2,3) (0,2) (0,1)
+        let buttons: Vec<BitSet> = [&[3][..], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]]
+            .iter()
+            .map(|b| lights(4, b))
+            .collect();
+        let target = lights(4, &[1, 2]);
+        let solution = solve(&buttons, &target).unwrap();
+        assert_eq!(2, solution.min_weight().count_ones());
+
+        let unreachable = solve(&[lights(2, &[0, 1])], &lights(2, &[0]));
+        assert!(unreachable.is_none());
+    }
+
+    /// Buttons toggling each light with probability 1/2.
+    fn random_buttons(width: usize, count: usize, seed: u64) -> Vec<BitSet> {
+        let mut rng = Rng::new(seed);
+        (0..count)
+            .map(|_| BitSet::from_indices(width, (0..width).filter(|_| rng.chance(1, 2))))
+            .collect()
+    }
+
+    fn press(buttons: &[BitSet], x: &BitSet) -> BitSet {
+        let mut lit = BitSet::new(buttons[0].len());
+        for j in x.ones() {
+            lit.xor_with(&buttons[j]);
+        }
+        lit
+    }
+
+    #[test]
+    fn test_min_weight_brute_force() {
+        for seed in 0..300 {
+            let width = 3 + seed as usize % 8;
+            let buttons = random_buttons(width, 2 + seed as usize % 11, seed);
+            let target = press(&buttons, &random_buttons(buttons.len(), 1, !seed)[0]);
+            let solution = solve(&buttons, &target).unwrap();
+
+            let k = solution.nullspace.len();
+            let brute = (0..1u32 << k)
+                .map(|mask| {
+                    let mut x = solution.particular.clone();
+                    for (i, v) in solution.nullspace.iter().enumerate() {
+                        if mask >> i & 1 == 1 {
+                            x.xor_with(v);
+                        }
+                    }
+                    x.count_ones()
+                })
+                .min()
+                .unwrap();
+            let best = solution.min_weight();
+            assert_eq!(brute, best.count_ones(), "seed {seed}");
+            assert_eq!(target, press(&buttons, &best));
+        }
+    }
+
+    #[test]
+    fn test_min_weight_large_nullity() {
+        // 100 lights and 140 buttons leave a nullity of 40; the target is three buttons away.
+        let buttons = random_buttons(100, 140, 7);
+        let target = press(&buttons, &BitSet::from_indices(140, [5, 77, 120]));
+        let solution = solve(&buttons, &target).unwrap();
+        assert_eq!(40, solution.nullspace.len());
+        let best = solution.min_weight();
+        assert_eq!(vec![5, 77, 120], best.ones().collect::<Vec<_>>());
+
+        // 12 lights and 150 buttons: a nullity of 138, searched by syndrome instead.
+        let buttons = random_buttons(12, 150, 11);
+        let target = press(&buttons, &BitSet::from_indices(150, [3, 40, 90, 149]));
+        let solution = solve(&buttons, &target).unwrap();
+        assert_eq!(138, solution.nullspace.len());
+        let best = solution.min_weight();
+        assert!(best.count_ones() <= 4);
+        assert_eq!(target, press(&buttons, &best));
+    }
+
+    #[test]
+    fn test_split_syndrome() {
+        // Every syndrome search has to agree on the weight with the other two.
+        for seed in 0..40 {
+            let width = 6 + seed as usize % 10;
+            let count = 3 * width + seed as usize % 20;
+            let buttons = random_buttons(width, count, seed);
+            let target = random_buttons(width, 1, !seed).remove(0);
+            let Some(solution) = solve(&buttons, &target) else {
+                continue;
+            };
+            let split = solution.min_weight_by_split_syndrome();
+            assert_eq!(target, press(&buttons, &split), "seed {seed}");
+            let weight = split.count_ones();
+            assert_eq!(weight, solution.min_weight_by_syndrome().count_ones());
+            assert_eq!(
+                weight,
+                solution.min_weight_by_information_sets().count_ones()
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_weight_mid_rank() {
+        // 30 lights and 150 buttons: too many syndromes for a table, too much nullity for
+        // information sets. A random target needs about six presses.
+        let buttons = random_buttons(30, 150, 3);
+        let target = random_buttons(30, 1, 5).remove(0);
+        let solution = solve(&buttons, &target).unwrap();
+        assert_eq!(120, solution.nullspace.len());
+        let best = solution.min_weight();
+        assert_eq!(target, press(&buttons, &best));
+        // Information sets agree, but take half a minute here.
+        assert_eq!(6, best.count_ones());
+    }
+}
@@ -0,0 +1,483 @@
+use crate::matrix::{Matrix, Solution};
+use crate::rational::Rational;
+
+/// Minimises `Σx` subject to `A·x = b`, `0 ≤ x ≤ upper` over the integers.
+///
+/// The equality constraints are first reduced exactly, which leaves one coordinate per free
+/// column to search. Every pivot variable then bounds a linear combination of the free ones, and
+/// so does the objective once a solution is known. Before each branch those constraints are
+/// propagated until the free variables' ranges stop shrinking, and a branch is cut when even the
+/// LP relaxation over what is left cannot beat the best so far. The variable with the fewest
+/// values left is branched on next. Returns `None` when no integer point is feasible.
+pub fn minimize_sum(a: &Matrix, b: &[Rational], upper: &[i128]) -> Option<Vec<i128>> {
+    assert_eq!(a.cols(), upper.len());
+    let solution = a.solve(b)?;
+    let search = Search::new(&solution, upper);
+
+    let domains = solution.free.iter().map(|&f| (0, upper[f])).collect();
+    let mut best = None;
+    search.branch(domains, &mut best);
+    best.map(|(_, x)| x)
+}
+
+/// `lo ≤ constant + Σ coeffs[d]·t[d] ≤ hi` over the free variables `t`.
+struct Constraint {
+    coeffs: Vec<Rational>,
+    constant: Rational,
+    lo: Rational,
+    hi: Rational,
+}
+
+struct Search<'a> {
+    solution: &'a Solution,
+    /// One per pivot variable, from `0 ≤ x[i] ≤ upper[i]`.
+    pivots: Vec<Constraint>,
+    /// `Σx` as a function of the free variables; its bounds are unused.
+    objective: Constraint,
+}
+
+impl<'a> Search<'a> {
+    fn new(solution: &'a Solution, upper: &[i128]) -> Self {
+        let row = |i: usize| -> Vec<Rational> { solution.nullspace.iter().map(|v| v[i]).collect() };
+        let pivots = solution
+            .pivots
+            .iter()
+            .map(|&i| Constraint {
+                coeffs: row(i),
+                constant: solution.particular[i],
+                lo: Rational::ZERO,
+                hi: Rational::from(upper[i]),
+            })
+            .collect();
+        let objective = Constraint {
+            coeffs: solution
+                .nullspace
+                .iter()
+                .map(|v| v.iter().fold(Rational::ZERO, |acc, &c| acc + c))
+                .collect(),
+            constant: solution
+                .particular
+                .iter()
+                .fold(Rational::ZERO, |acc, &x| acc + x),
+            lo: Rational::ZERO,
+            hi: Rational::ZERO,
+        };
+
+        Self {
+            solution,
+            pivots,
+            objective,
+        }
+    }
+
+    fn branch(&self, mut domains: Vec<(i128, i128)>, best: &mut Option<(i128, Vec<i128>)>) {
+        // `Σx` is an integer at every integer point, so only strictly smaller ones are wanted.
+        let cap = best
+            .as_ref()
+            .map(|(objective, _)| Rational::from(objective - 1));
+        if !self.propagate(&mut domains, cap) {
+            return;
+        }
+        let Some(bound) = self.relaxation(&domains) else {
+            return;
+        };
+        if cap.is_some_and(|cap| bound > cap) {
+            return;
+        }
+
+        let Some(depth) = (0..domains.len())
+            .filter(|&d| domains[d].0 < domains[d].1)
+            .min_by_key(|&d| domains[d].1 - domains[d].0)
+        else {
+            // Every free variable is fixed, but the pivots still have to be integers.
+            let values: Vec<Rational> = domains.iter().map(|&(v, _)| Rational::from(v)).collect();
+            if let Some(x) = self
+                .solution
+                .evaluate(&values)
+                .iter()
+                .map(Rational::to_integer)
+                .collect::<Option<Vec<i128>>>()
+            {
+                *best = Some((x.iter().sum(), x));
+            }
+            return;
+        };
+
+        // Try the cheaper end of the range first so good incumbents are found early.
+        let (lo, hi) = domains[depth];
+        let order: Box<dyn Iterator<Item = i128>> = if self.objective.coeffs[depth] < Rational::ZERO
+        {
+            Box::new((lo..=hi).rev())
+        } else {
+            Box::new(lo..=hi)
+        };
+        for val in order {
+            let mut next = domains.clone();
+            next[depth] = (val, val);
+            self.branch(next, best);
+        }
+    }
+
+    /// A lower bound on `Σx` over the real points inside `domains`, or `None` if there are none.
+    ///
+    /// The simplex runs in floating point, so it only picks a multiplier per pivot constraint.
+    /// The bound those multipliers give is then worked out exactly by [`Search::dual_bound`],
+    /// which holds for any multipliers at all: rounding can weaken it, but never cut off a point.
+    fn relaxation(&self, domains: &[(i128, i128)]) -> Option<Rational> {
+        // Shift to `s = t - lo ≥ 0` and write every bound as a `≤` row.
+        let n = domains.len();
+        let mut rows = Vec::new();
+        let mut rhs = Vec::new();
+        for constraint in &self.pivots {
+            let coeffs: Vec<f64> = constraint.coeffs.iter().map(Rational::to_f64).collect();
+            let at_lo = constraint.constant.to_f64()
+                + coeffs
+                    .iter()
+                    .zip(domains)
+                    .map(|(c, &(lo, _))| c * lo as f64)
+                    .sum::<f64>();
+            rows.push(coeffs.clone());
+            rhs.push(constraint.hi.to_f64() - at_lo);
+            rows.push(coeffs.iter().map(|c| -c).collect());
+            rhs.push(at_lo - constraint.lo.to_f64());
+        }
+        for (d, &(lo, hi)) in domains.iter().enumerate() {
+            let mut row = vec![0.0; n];
+            row[d] = 1.0;
+            rows.push(row);
+            rhs.push((hi - lo) as f64);
+        }
+
+        let gain: Vec<f64> = self.objective.coeffs.iter().map(Rational::to_f64).collect();
+        // The two rows of a pivot constraint combine into one multiplier on it.
+        let multipliers = |duals: Vec<f64>| -> Vec<Rational> {
+            duals
+                .chunks_exact(2)
+                .take(self.pivots.len())
+                .map(|pair| nearby(pair[0] - pair[1]))
+                .collect()
+        };
+        match simplex_min(&gain, &rows, &rhs) {
+            Simplex::Optimal(duals) => {
+                Some(self.dual_bound(domains, &multipliers(duals), Rational::ONE))
+            }
+            Simplex::Infeasible(duals) => {
+                if self.dual_bound(domains, &multipliers(duals), Rational::ZERO) > Rational::ZERO {
+                    return None;
+                }
+                Some(self.dual_bound(domains, &[], Rational::ONE))
+            }
+        }
+    }
+
+    /// Weak duality over the box: `weight·Σx` equals
+    /// `Σ y[k]·x[pivot k] + (weight·objective - Σ y[k]·pivot k)(t)` for any multipliers `y`, and
+    /// each pivot variable lies within its bounds, so taking both parts at their least over
+    /// `domains` bounds it from below. Missing multipliers count as zero. With a zero weight,
+    /// a positive result proves there is no point at all.
+    fn dual_bound(&self, domains: &[(i128, i128)], y: &[Rational], weight: Rational) -> Rational {
+        let mut coeffs: Vec<Rational> = self.objective.coeffs.iter().map(|&c| weight * c).collect();
+        let mut bound = weight * self.objective.constant;
+        for (constraint, &y) in self.pivots.iter().zip(y) {
+            if y.is_zero() {
+                continue;
+            }
+            bound += (y * constraint.lo).min(y * constraint.hi) - y * constraint.constant;
+            for (c, &a) in coeffs.iter_mut().zip(&constraint.coeffs) {
+                *c -= y * a;
+            }
+        }
+        for (&c, &(lo, hi)) in coeffs.iter().zip(domains) {
+            bound += (c * Rational::from(lo)).min(c * Rational::from(hi));
+        }
+        bound
+    }
+
+    /// Narrows `domains` to the values each constraint still allows given the others, with the
+    /// objective capped at `cap`. Returns `false` if some range becomes empty.
+    fn propagate(&self, domains: &mut [(i128, i128)], cap: Option<Rational>) -> bool {
+        let objective = cap.map(|hi| Constraint {
+            coeffs: self.objective.coeffs.clone(),
+            constant: self.objective.constant,
+            lo: Rational::ZERO,
+            hi,
+        });
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for constraint in self.pivots.iter().chain(&objective) {
+                let terms: Vec<(Rational, Rational)> = domains
+                    .iter()
+                    .zip(&constraint.coeffs)
+                    .map(|(&(lo, hi), &c)| {
+                        let (a, b) = (c * Rational::from(lo), c * Rational::from(hi));
+                        (a.min(b), a.max(b))
+                    })
+                    .collect();
+                let (mut min_sum, mut max_sum) = (constraint.constant, constraint.constant);
+                for &(a, b) in &terms {
+                    min_sum += a;
+                    max_sum += b;
+                }
+                if min_sum > constraint.hi || max_sum < constraint.lo {
+                    return false;
+                }
+
+                for d in 0..domains.len() {
+                    let c = constraint.coeffs[d];
+                    if c.is_zero() {
+                        continue;
+                    }
+                    // What `c·t[d]` may be once every other term is as helpful as it can be. The
+                    // sums are not updated as ranges shrink, which only leaves them looser.
+                    let (a, b) = terms[d];
+                    let low = constraint.lo - (max_sum - b);
+                    let high = constraint.hi - (min_sum - a);
+                    let (low, high) = if c > Rational::ZERO {
+                        (low / c, high / c)
+                    } else {
+                        (high / c, low / c)
+                    };
+                    let (lo, hi) = domains[d];
+                    let (new_lo, new_hi) = (lo.max(low.ceil()), hi.min(high.floor()));
+                    if new_lo > new_hi {
+                        return false;
+                    }
+                    if (new_lo, new_hi) != (lo, hi) {
+                        domains[d] = (new_lo, new_hi);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Slack for floating-point error inside the simplex, whose results are only ever used as
+/// multipliers for [`Search::dual_bound`].
+const TOLERANCE: f64 = 1e-6;
+
+/// Multipliers are rounded to multiples of `1 / MULTIPLIER_SCALE`.
+const MULTIPLIER_SCALE: i128 = 1 << 20;
+
+/// A fraction near `y` with a small denominator, or zero if `y` is not a usable number.
+fn nearby(y: f64) -> Rational {
+    if !y.is_finite() || y.abs() > 1e9 {
+        return Rational::ZERO;
+    }
+    Rational::new(
+        (y * MULTIPLIER_SCALE as f64).round() as i128,
+        MULTIPLIER_SCALE,
+    )
+}
+
+/// How the simplex ended, with one dual value per row: at an optimum they are the dual
+/// solution, and when infeasible they come from phase 1 and combine the rows into one that
+/// no point satisfies. Both are only as accurate as floating point allows.
+enum Simplex {
+    Optimal(Vec<f64>),
+    Infeasible(Vec<f64>),
+}
+
+/// Minimises `c·x` subject to `rows·x ≤ rhs`, `x ≥ 0` with a two-phase tableau simplex, using
+/// Bland's rule so it cannot cycle. The caller keeps it bounded.
+fn simplex_min(c: &[f64], rows: &[Vec<f64>], rhs: &[f64]) -> Simplex {
+    let (m, n) = (rows.len(), c.len());
+    // Columns: the variables, one slack per row, one artificial per row, then the right-hand side.
+    let width = n + 2 * m + 1;
+    let mut tableau = vec![vec![0.0; width]; m];
+    let mut basis = vec![0; m];
+    for i in 0..m {
+        // Rows with a negative right-hand side are negated, so their slack cannot start basic.
+        let sign = if rhs[i] < 0.0 { -1.0 } else { 1.0 };
+        for j in 0..n {
+            tableau[i][j] = sign * rows[i][j];
+        }
+        tableau[i][n + i] = sign;
+        tableau[i][width - 1] = sign * rhs[i];
+        if sign < 0.0 {
+            tableau[i][n + m + i] = 1.0;
+            basis[i] = n + m + i;
+        } else {
+            basis[i] = n + i;
+        }
+    }
+
+    // Phase 1 drives the artificials to zero, phase 2 optimises `c` without them.
+    let artificial = |j: usize| (n + m..n + 2 * m).contains(&j);
+    let phase1: Vec<f64> = (0..width - 1)
+        .map(|j| if artificial(j) { 1.0 } else { 0.0 })
+        .collect();
+    // The dual value of a row is the cost of its slack column as the tableau now reads it.
+    let duals = |tableau: &[Vec<f64>], basis: &[usize], cost: &[f64]| -> Vec<f64> {
+        (0..m)
+            .map(|i| {
+                tableau
+                    .iter()
+                    .zip(basis)
+                    .map(|(row, &b)| cost[b] * row[n + i])
+                    .sum()
+            })
+            .collect()
+    };
+    if run_simplex(&mut tableau, &mut basis, &phase1, |_| true) > TOLERANCE {
+        return Simplex::Infeasible(duals(&tableau, &basis, &phase1));
+    }
+    let mut phase2 = vec![0.0; width - 1];
+    phase2[..n].copy_from_slice(c);
+    run_simplex(&mut tableau, &mut basis, &phase2, |j| !artificial(j));
+    Simplex::Optimal(duals(&tableau, &basis, &phase2))
+}
+
+/// Pivots until no allowed column improves `cost·x`, returning the optimum.
+fn run_simplex(
+    tableau: &mut [Vec<f64>],
+    basis: &mut [usize],
+    cost: &[f64],
+    allowed: impl Fn(usize) -> bool,
+) -> f64 {
+    let last = cost.len();
+    loop {
+        let reduced = |j: usize| {
+            cost[j]
+                - tableau
+                    .iter()
+                    .zip(basis.iter())
+                    .map(|(row, &b)| cost[b] * row[j])
+                    .sum::<f64>()
+        };
+        let Some(enter) = (0..last).find(|&j| allowed(j) && reduced(j) < -TOLERANCE) else {
+            return tableau
+                .iter()
+                .zip(basis.iter())
+                .map(|(row, &b)| cost[b] * row[last])
+                .sum();
+        };
+        let Some(leave) = (0..tableau.len())
+            .filter(|&i| tableau[i][enter] > TOLERANCE)
+            .min_by(|&a, &b| {
+                let ratio = |i: usize| tableau[i][last] / tableau[i][enter];
+                ratio(a).total_cmp(&ratio(b)).then(basis[a].cmp(&basis[b]))
+            })
+        else {
+            return f64::NEG_INFINITY;
+        };
+
+        let pivot = tableau[leave][enter];
+        for v in tableau[leave].iter_mut() {
+            *v /= pivot;
+        }
+        let pivot_row = tableau[leave].clone();
+        for (i, row) in tableau.iter_mut().enumerate() {
+            let factor = row[enter];
+            if i != leave && factor != 0.0 {
+                for (v, p) in row.iter_mut().zip(&pivot_row) {
+                    *v -= factor * p;
+                }
+            }
+        }
+        basis[leave] = enter;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    fn buttons_matrix(counters: usize, buttons: &[&[usize]]) -> Matrix {
+        let mut a = Matrix::zeros(counters, buttons.len());
+        for (j, button) in buttons.iter().enumerate() {
+            for &r in *button {
+                a[(r, j)] = Rational::ONE;
+            }
+        }
+        a
+    }
+
+    #[test]
+    fn test_minimize_sum() {
+        // (3) (1,3) (2) (2,3) (0,2) (0,1) {3,5,4,7}
+        let a = buttons_matrix(4, &[&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]]);
+        let b: Vec<Rational> = [3i64, 5, 4, 7].map(Rational::from).to_vec();
+        let x = minimize_sum(&a, &b, &[7, 5, 4, 4, 3, 3]).unwrap();
+        assert_eq!(10, x.iter().sum::<i128>());
+
+        let ints: Vec<Rational> = x.iter().map(|&v| Rational::from(v)).collect();
+        assert_eq!(b, a.mul_vec(&ints));
+    }
+
+    #[test]
+    fn test_dual_bound() {
+        let a = buttons_matrix(4, &[&[3], &[1, 3], &[2], &[2, 3], &[0, 2], &[0, 1]]);
+        let b: Vec<Rational> = [3i64, 5, 4, 7].map(Rational::from).to_vec();
+        let upper = [7, 5, 4, 4, 3, 3];
+        let solution = a.solve(&b).unwrap();
+        let search = Search::new(&solution, &upper);
+        let domains: Vec<(i128, i128)> = solution.free.iter().map(|&f| (0, upper[f])).collect();
+
+        // Any multipliers at all give a bound no higher than the optimum of 10.
+        let bound = search.relaxation(&domains).unwrap();
+        assert_eq!(10, bound.ceil());
+        let mut rng = Rng::new(28);
+        for _ in 0..50 {
+            let y: Vec<Rational> = (0..search.pivots.len())
+                .map(|_| Rational::new(rng.below(23) as i128 - 11, 1 + rng.below(5) as i128))
+                .collect();
+            assert!(search.dual_bound(&domains, &y, Rational::ONE) <= Rational::from(10i64));
+            assert!(search.dual_bound(&domains, &y, Rational::ZERO) <= Rational::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_brute_force() {
+        let mut rng = Rng::new(1);
+        let mut next = |n: u64| rng.below(n);
+        for _ in 0..200 {
+            let counters = 1 + next(3) as usize;
+            let buttons: Vec<Vec<usize>> = (0..2 + next(3))
+                .map(|_| (0..counters).filter(|_| next(2) == 0).collect())
+                .collect();
+            let refs: Vec<&[usize]> = buttons.iter().map(Vec::as_slice).collect();
+            let a = buttons_matrix(counters, &refs);
+            let b: Vec<Rational> = (0..counters)
+                .map(|_| Rational::from(next(8) as i128))
+                .collect();
+            let upper: Vec<i128> = buttons.iter().map(|_| next(6) as i128).collect();
+
+            // Every point of the box, as mixed-radix digits.
+            let points: i128 = upper.iter().map(|u| u + 1).product();
+            let brute = (0..points)
+                .map(|mut p| {
+                    upper
+                        .iter()
+                        .map(|u| {
+                            let v = p % (u + 1);
+                            p /= u + 1;
+                            v
+                        })
+                        .collect::<Vec<i128>>()
+                })
+                .filter(|x| {
+                    let x: Vec<Rational> = x.iter().map(|&v| Rational::from(v)).collect();
+                    a.mul_vec(&x) == b
+                })
+                .map(|x| x.iter().sum::<i128>())
+                .min();
+            let found = minimize_sum(&a, &b, &upper).map(|x| x.iter().sum::<i128>());
+            assert_eq!(brute, found, "{buttons:?} {b:?} {upper:?}");
+        }
+    }
+
+    #[test]
+    fn test_infeasible() {
+        // x0 + x1 = 1 and x0 + x1 = 2 is inconsistent; x0 + x1 = 3 with both capped at 1 is out of bounds.
+        let a = buttons_matrix(2, &[&[0, 1], &[0, 1]]);
+        let b = [Rational::ONE, Rational::from(2i64)];
+        assert!(minimize_sum(&a, &b, &[5, 5]).is_none());
+
+        let a = buttons_matrix(1, &[&[0], &[0]]);
+        assert!(minimize_sum(&a, &[Rational::from(3i64)], &[1, 1]).is_none());
+    }
+}
@@ -1,3 +1,15 @@
+pub mod bitgrid;
+pub mod digits;
+pub mod gf2;
+pub mod ilp;
+pub mod matrix;
+pub mod peeling;
+pub mod polyomino;
+pub mod ranges;
+pub mod rational;
+pub mod rng;
+pub mod terminal;
+
 pub fn start_day(day: &str) {
     println!("Advent of Code 2025 - Day {:0>2}", day);
 }
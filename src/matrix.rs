@@ -0,0 +1,269 @@
+use crate::rational::Rational;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A dense matrix over exact rationals, so elimination never has to guess whether a value is zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix {
+    data: Vec<Vec<Rational>>,
+    rows: usize,
+    cols: usize,
+}
+
+/// Reduced row echelon form of a matrix together with its pivot structure.
+#[derive(Debug, Clone)]
+pub struct Echelon {
+    pub reduced: Matrix,
+    /// Pivot column of each non-zero row, in row order.
+    pub pivots: Vec<usize>,
+    /// Columns without a pivot, i.e. the free variables of `A·x = 0`.
+    pub free: Vec<usize>,
+}
+
+/// Every solution of `A·x = b` is `particular + Σ tᵢ·nullspace[i]` for arbitrary `tᵢ`.
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub particular: Vec<Rational>,
+    /// One basis vector per free column, with a 1 in that column.
+    pub nullspace: Vec<Vec<Rational>>,
+    pub pivots: Vec<usize>,
+    pub free: Vec<usize>,
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self {
+            data: vec![vec![Rational::ZERO; cols]; rows],
+            rows,
+            cols,
+        }
+    }
+
+    pub fn from_rows<T: Into<Rational> + Copy>(rows: &[Vec<T>]) -> Self {
+        let cols = rows.first().map_or(0, |r| r.len());
+        assert!(rows.iter().all(|r| r.len() == cols), "ragged rows");
+        Self {
+            data: rows
+                .iter()
+                .map(|r| r.iter().map(|&v| v.into()).collect())
+                .collect(),
+            rows: rows.len(),
+            cols,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, r: usize) -> &[Rational] {
+        &self.data[r]
+    }
+
+    /// `[self | b]`, the augmented matrix of `self·x = b`.
+    pub fn augment(&self, b: &[Rational]) -> Matrix {
+        assert_eq!(self.rows, b.len());
+        let mut m = Matrix::zeros(self.rows, self.cols + 1);
+        for (r, row) in self.data.iter().enumerate() {
+            m.data[r][..self.cols].copy_from_slice(row);
+            m.data[r][self.cols] = b[r];
+        }
+        m
+    }
+
+    pub fn mul_vec(&self, x: &[Rational]) -> Vec<Rational> {
+        assert_eq!(self.cols, x.len());
+        self.data
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(x)
+                    .fold(Rational::ZERO, |acc, (&a, &b)| acc + a * b)
+            })
+            .collect()
+    }
+
+    pub fn rref(&self) -> Echelon {
+        self.rref_limited(self.cols)
+    }
+
+    // Gauss-Jordan elimination that only picks pivots among the first `limit` columns, so an
+    // augmented column is reduced alongside the coefficients but never becomes a pivot itself.
+    fn rref_limited(&self, limit: usize) -> Echelon {
+        let mut m = self.clone();
+        let mut pivots = Vec::new();
+        let mut free = Vec::new();
+        let mut pivot = 0;
+
+        for col in 0..limit {
+            if pivot == m.rows {
+                free.push(col);
+                continue;
+            }
+            let Some(best) = (pivot..m.rows).find(|&r| !m.data[r][col].is_zero()) else {
+                free.push(col);
+                continue;
+            };
+            m.data.swap(pivot, best);
+
+            let inv = m.data[pivot][col].recip();
+            for val in &mut m.data[pivot][col..] {
+                *val = *val * inv;
+            }
+
+            let pivot_row = m.data[pivot].clone();
+            for (r, row) in m.data.iter_mut().enumerate() {
+                let factor = row[col];
+                if r == pivot || factor.is_zero() {
+                    continue;
+                }
+                for (val, &p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *val -= factor * p;
+                }
+            }
+
+            pivots.push(col);
+            pivot += 1;
+        }
+
+        Echelon {
+            reduced: m,
+            pivots,
+            free,
+        }
+    }
+
+    /// Solves `self·x = b` exactly, returning `None` when the system is inconsistent.
+    pub fn solve(&self, b: &[Rational]) -> Option<Solution> {
+        let echelon = self.augment(b).rref_limited(self.cols);
+        let rank = echelon.rank();
+
+        // A zero coefficient row with a non-zero right-hand side means 0 = c.
+        if (rank..self.rows).any(|r| !echelon.reduced.data[r][self.cols].is_zero()) {
+            return None;
+        }
+
+        let mut particular = vec![Rational::ZERO; self.cols];
+        for (r, &col) in echelon.pivots.iter().enumerate() {
+            particular[col] = echelon.reduced.data[r][self.cols];
+        }
+
+        Some(Solution {
+            particular,
+            nullspace: echelon.nullspace_limited(self.cols),
+            pivots: echelon.pivots,
+            free: echelon.free,
+        })
+    }
+}
+
+impl Echelon {
+    pub fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+
+    pub fn nullspace(&self) -> Vec<Vec<Rational>> {
+        self.nullspace_limited(self.reduced.cols)
+    }
+
+    fn nullspace_limited(&self, cols: usize) -> Vec<Vec<Rational>> {
+        self.free
+            .iter()
+            .map(|&f| {
+                let mut v = vec![Rational::ZERO; cols];
+                v[f] = Rational::ONE;
+                for (r, &p) in self.pivots.iter().enumerate() {
+                    v[p] = -self.reduced.data[r][f];
+                }
+                v
+            })
+            .collect()
+    }
+}
+
+impl Solution {
+    /// The solution obtained by giving the free variables (in `self.free` order) these values.
+    pub fn evaluate(&self, free_values: &[Rational]) -> Vec<Rational> {
+        assert_eq!(self.free.len(), free_values.len());
+        let mut x = self.particular.clone();
+        for (basis, &t) in self.nullspace.iter().zip(free_values) {
+            if t.is_zero() {
+                continue;
+            }
+            for (xi, &bi) in x.iter_mut().zip(basis) {
+                *xi += t * bi;
+            }
+        }
+        x
+    }
+}
+
+impl Index<(usize, usize)> for Matrix {
+    type Output = Rational;
+
+    fn index(&self, (r, c): (usize, usize)) -> &Rational {
+        &self.data[r][c]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut Rational {
+        &mut self.data[r][c]
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.data {
+            let cells: Vec<String> = row.iter().map(|v| format!("{v:>5}")).collect();
+            writeln!(f, "[{}]", cells.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ints(v: &[i64]) -> Vec<Rational> {
+        v.iter().map(|&x| x.into()).collect()
+    }
+
+    #[test]
+    fn test_rref() {
+        let m = Matrix::from_rows(&[vec![1i64, 2, 3], vec![2, 4, 6], vec![1, 0, 1]]);
+        let e = m.rref();
+        assert_eq!(2, e.rank());
+        assert_eq!(vec![0, 1], e.pivots);
+        assert_eq!(vec![2], e.free);
+
+        let null = e.nullspace();
+        assert_eq!(vec![ints(&[-1, -1, 1])], null);
+        assert!(m.mul_vec(&null[0]).iter().all(Rational::is_zero));
+    }
+
+    #[test]
+    fn test_solve() {
+        // The first machine of the day 10 example: buttons are columns, counters are rows.
+        let a = Matrix::from_rows(&[
+            vec![0i64, 0, 0, 0, 1, 1],
+            vec![0, 1, 0, 0, 0, 1],
+            vec![0, 0, 1, 1, 1, 0],
+            vec![1, 1, 0, 1, 0, 0],
+        ]);
+        let b = ints(&[3, 5, 4, 7]);
+        let s = a.solve(&b).unwrap();
+        assert_eq!(2, s.free.len());
+
+        let x = s.evaluate(&ints(&[1, 3]));
+        assert_eq!(b, a.mul_vec(&x));
+
+        let inconsistent = Matrix::from_rows(&[vec![1i64, 1], vec![2, 2]]);
+        assert!(inconsistent.solve(&ints(&[1, 3])).is_none());
+    }
+}
@@ -0,0 +1,579 @@
+use crate::bitgrid::BitGrid;
+use anyhow::{ensure, Result};
+use std::io::BufRead;
+
+/// Which cells count as "around" a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The 4 orthogonal cells.
+    VonNeumann,
+    /// The 8 orthogonal and diagonal cells.
+    Moore,
+    /// Every other cell within `r` steps in both directions, the `(2r + 1)²` square around it.
+    Radius(usize),
+}
+
+impl Neighbourhood {
+    /// `(dr, dc)` offsets, never `(0, 0)`.
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        let r = match self {
+            Neighbourhood::VonNeumann | Neighbourhood::Moore => 1,
+            Neighbourhood::Radius(r) => *r as isize,
+        };
+        let mut offsets = vec![];
+        for dr in -r..=r {
+            for dc in -r..=r {
+                let keep = match self {
+                    Neighbourhood::VonNeumann => dr.abs() + dc.abs() == 1,
+                    _ => (dr, dc) != (0, 0),
+                };
+                if keep {
+                    offsets.push((dr, dc));
+                }
+            }
+        }
+        offsets
+    }
+}
+
+/// What lies past the edge of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// Nothing: edge cells just have fewer neighbours.
+    Finite,
+    /// The opposite edge, as on a torus.
+    Toroidal,
+}
+
+/// A roll is removed while fewer than `threshold` rolls are around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub threshold: usize,
+    pub neighbourhood: Neighbourhood,
+    pub boundary: Boundary,
+}
+
+impl Default for Rules {
+    /// The day 04 forklift rule: fewer than 4 of the 8 surrounding cells, no wrapping.
+    fn default() -> Self {
+        Self {
+            threshold: 4,
+            neighbourhood: Neighbourhood::Moore,
+            boundary: Boundary::Finite,
+        }
+    }
+}
+
+/// The neighbours of each cell under some `Rules`, for one grid size.
+struct Neighbours {
+    offsets: Vec<(isize, isize)>,
+    boundary: Boundary,
+    rows: usize,
+    cols: usize,
+}
+
+impl Neighbours {
+    /// On a torus smaller than the neighbourhood, a cell can be reached through several
+    /// offsets and is then counted once per offset; offsets that wrap back onto the cell
+    /// itself are skipped.
+    fn of(&self, r: usize, c: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.offsets.iter().filter_map(move |&(dr, dc)| {
+            let cell = match self.boundary {
+                Boundary::Finite => (
+                    r.checked_add_signed(dr).filter(|&nr| nr < self.rows)?,
+                    c.checked_add_signed(dc).filter(|&nc| nc < self.cols)?,
+                ),
+                Boundary::Toroidal => (
+                    (r as isize + dr).rem_euclid(self.rows as isize) as usize,
+                    (c as isize + dc).rem_euclid(self.cols as isize) as usize,
+                ),
+            };
+            (cell != (r, c)).then_some(cell)
+        })
+    }
+}
+
+/// Reads `@` as a roll (1) and anything else as empty (0), skipping blank lines.
+pub fn parse_grid<R: BufRead>(reader: R) -> Result<Vec<Vec<u8>>> {
+    let mut matrix: Vec<Vec<u8>> = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            continue;
+        }
+        let row: Vec<u8> = line.chars().map(|c| if c == '@' { 1 } else { 0 }).collect();
+        if let Some(first) = matrix.first() {
+            ensure!(
+                row.len() == first.len(),
+                "row {} has {} cells, expected {}",
+                matrix.len() + 1,
+                row.len(),
+                first.len()
+            );
+        }
+        matrix.push(row);
+    }
+    Ok(matrix)
+}
+
+/// The outcome of peeling a grid: when each roll went and what is left.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peeling {
+    pub total: usize,
+    /// The 1-based wave each roll was removed in; `None` for empty cells and rolls that stay.
+    pub removed_in: Vec<Vec<Option<usize>>>,
+    /// `wave_counts[w - 1]` rolls went in wave `w`.
+    pub wave_counts: Vec<usize>,
+    /// The grid once no roll can be removed.
+    pub stable: Vec<Vec<u8>>,
+}
+
+impl Peeling {
+    /// One character per cell: `.` empty, `@` never removed, and the wave in base 36 (`1`-`9`,
+    /// then `a`-`z`), with `#` past wave 35.
+    pub fn heatmap(&self) -> String {
+        let mut out = String::new();
+        for (r, row) in self.removed_in.iter().enumerate() {
+            for (c, wave) in row.iter().enumerate() {
+                out.push(match wave {
+                    Some(w) => u32::try_from(*w)
+                        .ok()
+                        .and_then(|w| char::from_digit(w, 36))
+                        .unwrap_or('#'),
+                    None if self.stable[r][c] == 1 => '@',
+                    None => '.',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The wave of each cell as CSV rows matching the grid: the wave number, `never` for rolls
+    /// that stay, and an empty field for empty cells.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        for (r, row) in self.removed_in.iter().enumerate() {
+            let fields: Vec<String> = row
+                .iter()
+                .enumerate()
+                .map(|(c, wave)| match wave {
+                    Some(w) => w.to_string(),
+                    None if self.stable[r][c] == 1 => "never".to_string(),
+                    None => String::new(),
+                })
+                .collect();
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// `wave,removed,remaining` per wave, after a header line.
+    pub fn waves_csv(&self) -> String {
+        let mut remaining: usize = self.stable.iter().flatten().map(|&v| v as usize).sum();
+        remaining += self.total;
+        let mut out = String::from("wave,removed,remaining\n");
+        for (w, &count) in self.wave_counts.iter().enumerate() {
+            remaining -= count;
+            out.push_str(&format!("{},{},{}\n", w + 1, count, remaining));
+        }
+        out
+    }
+}
+
+/// Removes rolls with fewer than `rules.threshold` rolls around them until none is left.
+///
+/// Each roll keeps a count of its neighbours, and only the neighbours of a removed roll are
+/// re-checked, so every cell is touched a constant number of times per neighbour. Rolls are
+/// taken a wave at a time: one whose count drops while wave `w` is removed goes in wave
+/// `w + 1`, exactly as with a full rescan after every wave.
+pub fn peel(matrix: &[Vec<u8>], rules: &Rules) -> Peeling {
+    let mut matrix = matrix.to_vec();
+    let row_count = matrix.len();
+    let col_count = matrix.first().map_or(0, |row| row.len());
+    let neighbours = Neighbours {
+        offsets: rules.neighbourhood.offsets(),
+        boundary: rules.boundary,
+        rows: row_count,
+        cols: col_count,
+    };
+    let mut count_around = vec![vec![0usize; col_count]; row_count];
+    let mut removed_in = vec![vec![None; col_count]; row_count];
+    let mut queued = vec![vec![false; col_count]; row_count];
+    let mut wave = vec![];
+
+    for r in 0..row_count {
+        for c in 0..col_count {
+            if matrix[r][c] == 0 {
+                continue;
+            }
+            count_around[r][c] = neighbours
+                .of(r, c)
+                .map(|(nr, nc)| matrix[nr][nc] as usize)
+                .sum();
+            if count_around[r][c] < rules.threshold {
+                queued[r][c] = true;
+                wave.push((r, c));
+            }
+        }
+    }
+
+    let mut wave_counts = vec![];
+    while !wave.is_empty() {
+        for &(r, c) in &wave {
+            matrix[r][c] = 0;
+            removed_in[r][c] = Some(wave_counts.len() + 1);
+        }
+        wave_counts.push(wave.len());
+
+        let mut next = vec![];
+        for (r, c) in wave {
+            for (nr, nc) in neighbours.of(r, c) {
+                if matrix[nr][nc] == 0 {
+                    continue;
+                }
+                count_around[nr][nc] -= 1;
+                if count_around[nr][nc] < rules.threshold && !queued[nr][nc] {
+                    queued[nr][nc] = true;
+                    next.push((nr, nc));
+                }
+            }
+        }
+        wave = next;
+    }
+
+    Peeling {
+        total: wave_counts.iter().sum(),
+        removed_in,
+        wave_counts,
+        stable: matrix,
+    }
+}
+
+/// Like [`parse_grid`], but straight into a [`BitGrid`] so a huge input never exists as bytes.
+pub fn parse_packed<R: BufRead>(reader: R) -> Result<BitGrid> {
+    let mut rows: Vec<Vec<u64>> = vec![];
+    let mut cols = None;
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim_end_matches('\r').as_bytes();
+        if line.is_empty() {
+            continue;
+        }
+        let expected = *cols.get_or_insert(line.len());
+        ensure!(
+            line.len() == expected,
+            "row {} has {} cells, expected {expected}",
+            rows.len() + 1,
+            line.len()
+        );
+        let mut row = vec![0u64; expected.div_ceil(64)];
+        for (c, _) in line.iter().enumerate().filter(|&(_, &b)| b == b'@') {
+            row[c / 64] |= 1 << (c % 64);
+        }
+        rows.push(row);
+    }
+
+    let mut grid = BitGrid::new(rows.len(), cols.unwrap_or(0));
+    for (r, row) in rows.into_iter().enumerate() {
+        grid.row_mut(r).copy_from_slice(&row);
+    }
+    Ok(grid)
+}
+
+/// The outcome of [`peel_packed`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedPeeling {
+    pub total: usize,
+    pub wave_counts: Vec<usize>,
+    pub stable: BitGrid,
+}
+
+/// Adds one bit plane into a bit-sliced counter: bit `i` of `planes[k]` is bit `k` of the count
+/// for cell `i`.
+#[inline]
+fn add_plane(planes: &mut [u64; 4], mut carry: u64) {
+    for plane in planes.iter_mut() {
+        let sum = *plane ^ carry;
+        carry &= *plane;
+        *plane = sum;
+    }
+}
+
+/// The cells of a word whose bit-sliced count is below `threshold`.
+#[inline]
+fn below(planes: &[u64; 4], threshold: usize) -> u64 {
+    (0..threshold.min(16)).fold(0, |acc, value| {
+        acc | planes.iter().enumerate().fold(u64::MAX, |eq, (k, &plane)| {
+            eq & if value >> k & 1 == 1 { plane } else { !plane }
+        })
+    })
+}
+
+/// [`peel`] with the default neighbourhood and boundary (Moore, finite) on a [`BitGrid`].
+///
+/// Only that much of [`Rules`] is supported: the 8 surrounding cells, nothing past the edges,
+/// and removal below `threshold`. Other neighbourhoods or a toroidal boundary need [`peel`].
+///
+/// The 8 neighbours of 64 cells at once are the words of the rows above, at and below, each
+/// shifted one column either way, summed into 4 bit planes. A wave only revisits the rows around
+/// the previous wave's removals, at most O(waves × cells / 64), with one bit per cell instead of
+/// the byte grid and the per-cell counts.
+pub fn peel_packed(grid: &BitGrid, threshold: usize) -> PackedPeeling {
+    let mut grid = grid.clone();
+    let (rows, stride) = (grid.rows(), grid.stride());
+    let zeros = vec![0u64; stride];
+    let mut removable = BitGrid::new(rows, grid.cols());
+    let mut wave_counts = vec![];
+    // Only rows next to a row that lost rolls can change in the next wave.
+    let mut candidates: Vec<usize> = (0..rows).collect();
+
+    loop {
+        let mut count = 0;
+        let mut changed = vec![];
+        for &r in &candidates {
+            let above = if r > 0 { grid.row(r - 1) } else { &zeros };
+            let below_row = if r + 1 < rows {
+                grid.row(r + 1)
+            } else {
+                &zeros
+            };
+            let row = grid.row(r);
+            let mut row_count = 0;
+            for i in 0..stride {
+                let mut planes = [0u64; 4];
+                for (dr, words) in [(-1, above), (0, row), (1, below_row)] {
+                    let word = words[i];
+                    let prev = if i > 0 { words[i - 1] } else { 0 };
+                    let next = words.get(i + 1).copied().unwrap_or(0);
+                    // Column c - 1 and c + 1 moved onto bit c.
+                    add_plane(&mut planes, word << 1 | prev >> 63);
+                    add_plane(&mut planes, word >> 1 | next << 63);
+                    if dr != 0 {
+                        add_plane(&mut planes, word);
+                    }
+                }
+                let gone = row[i] & below(&planes, threshold);
+                removable.row_mut(r)[i] = gone;
+                row_count += gone.count_ones() as usize;
+            }
+            if row_count > 0 {
+                changed.push(r);
+                count += row_count;
+            }
+        }
+        if count == 0 {
+            break;
+        }
+
+        candidates.clear();
+        for &r in &changed {
+            for i in 0..stride {
+                let gone = removable.row(r)[i];
+                grid.row_mut(r)[i] &= !gone;
+            }
+            for near in r.saturating_sub(1)..(r + 2).min(rows) {
+                if candidates.last().is_none_or(|&last| last < near) {
+                    candidates.push(near);
+                }
+            }
+        }
+        wave_counts.push(count);
+    }
+
+    PackedPeeling {
+        total: wave_counts.iter().sum(),
+        wave_counts,
+        stable: grid,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// The day 04 example.
+    const EXAMPLE: &str = "\
+..@@.@@@@.
+@@@.@.@.@@
+@@@@@.@.@@
+@.@@@@..@.
+@@.@@@@.@@
+.@@@@@@@.@
+.@.@.@.@@@
+@.@@@.@@@@
+.@@@@@@@@.
+@.@.@@@.@.
+";
+
+    /// A seeded grid with about 70% rolls.
+    fn sample_grid(rows: usize, cols: usize, seed: u64) -> Vec<Vec<u8>> {
+        let mut rng = Rng::new(seed);
+        (0..rows)
+            .map(|_| (0..cols).map(|_| rng.chance(7, 10) as u8).collect())
+            .collect()
+    }
+
+    /// Wave counts and the stable grid from rescanning with [`step`] until nothing changes.
+    fn rescan(mut matrix: Vec<Vec<u8>>, rules: &Rules) -> (Vec<usize>, Vec<Vec<u8>>) {
+        let mut counts = vec![];
+        loop {
+            let t = step(&mut matrix, rules);
+            if t == 0 {
+                return (counts, matrix);
+            }
+            counts.push(t);
+        }
+    }
+
+    /// One synchronous wave by direct counting.
+    fn step(matrix: &mut [Vec<u8>], rules: &Rules) -> usize {
+        let (rows, cols) = (matrix.len(), matrix[0].len());
+        let neighbours = Neighbours {
+            offsets: rules.neighbourhood.offsets(),
+            boundary: rules.boundary,
+            rows,
+            cols,
+        };
+        let mut removable = vec![];
+        for r in 0..rows {
+            for c in 0..cols {
+                let around: usize = neighbours
+                    .of(r, c)
+                    .map(|(nr, nc)| matrix[nr][nc] as usize)
+                    .sum();
+                if matrix[r][c] == 1 && around < rules.threshold {
+                    removable.push((r, c));
+                }
+            }
+        }
+        for &(r, c) in &removable {
+            matrix[r][c] = 0;
+        }
+        removable.len()
+    }
+
+    #[test]
+    fn test_offsets() {
+        assert_eq!(4, Neighbourhood::VonNeumann.offsets().len());
+        assert_eq!(8, Neighbourhood::Moore.offsets().len());
+        assert_eq!(
+            Neighbourhood::Moore.offsets(),
+            Neighbourhood::Radius(1).offsets()
+        );
+        assert_eq!(24, Neighbourhood::Radius(2).offsets().len());
+        assert!(Neighbourhood::Radius(0).offsets().is_empty());
+    }
+
+    #[test]
+    fn test_peel() {
+        let matrix = parse_grid(EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(43, peel(&matrix, &Rules::default()).total);
+
+        // Each rescan pass is one wave.
+        for seed in 0..20u64 {
+            let matrix = sample_grid(10 + seed as usize, 30 - seed as usize, seed);
+            let peeling = peel(&matrix, &Rules::default());
+            let (counts, stable) = rescan(matrix, &Rules::default());
+            assert_eq!(counts, peeling.wave_counts);
+            assert_eq!(stable, peeling.stable);
+        }
+        assert_eq!(0, peel(&[], &Rules::default()).total);
+    }
+
+    #[test]
+    fn test_rules() {
+        let all_rules = [
+            (Neighbourhood::VonNeumann, 2),
+            (Neighbourhood::Moore, 4),
+            (Neighbourhood::Moore, 5),
+            (Neighbourhood::Radius(2), 11),
+        ]
+        .into_iter()
+        .flat_map(|(neighbourhood, threshold)| {
+            [Boundary::Finite, Boundary::Toroidal].map(|boundary| Rules {
+                threshold,
+                neighbourhood,
+                boundary,
+            })
+        });
+        for rules in all_rules {
+            for seed in 0..10u64 {
+                let matrix = sample_grid(3 + seed as usize, 17 - seed as usize, seed);
+                let peeling = peel(&matrix, &rules);
+                let (counts, stable) = rescan(matrix, &rules);
+                assert_eq!(counts, peeling.wave_counts, "{rules:?} seed {seed}");
+                assert_eq!(stable, peeling.stable);
+            }
+        }
+
+        // A full torus has no edge to start from.
+        let solid = vec![vec![1u8; 5]; 5];
+        let torus = Rules {
+            boundary: Boundary::Toroidal,
+            ..Rules::default()
+        };
+        assert_eq!(0, peel(&solid, &torus).total);
+        assert_eq!(4, peel(&solid, &Rules::default()).total);
+    }
+
+    #[test]
+    fn test_outputs() {
+        let matrix = parse_grid("@@@@@\n@@@@@\n@@@.@\n".as_bytes()).unwrap();
+        let peeling = peel(&matrix, &Rules::default());
+        assert_eq!(vec![4, 2, 3, 4, 1], peeling.wave_counts);
+        assert_eq!("13431\n24542\n134.1\n", peeling.heatmap());
+        assert_eq!("1,3,4,3,1\n2,4,5,4,2\n1,3,4,,1\n", peeling.to_csv());
+        assert!(peeling
+            .waves_csv()
+            .starts_with("wave,removed,remaining\n1,4,10\n"));
+        assert!(peeling.waves_csv().ends_with("4,4,1\n5,1,0\n"));
+
+        let solid = vec![vec![1u8; 4]; 4];
+        assert_eq!(
+            "1@@1\n@@@@\n@@@@\n1@@1\n",
+            peel(&solid, &Rules::default()).heatmap()
+        );
+        assert!(peel(&solid, &Rules::default())
+            .to_csv()
+            .starts_with("1,never,never,1\n"));
+    }
+
+    #[test]
+    fn test_peel_packed() {
+        // Widths around word boundaries, then one grid with many waves.
+        for seed in 0..7u64 {
+            let (rows, cols) = [(40, 1), (47, 63), (54, 64), (61, 65), (68, 128), (75, 200)]
+                .get(seed as usize)
+                .copied()
+                .unwrap_or((300, 500));
+            let matrix = sample_grid(rows, cols, seed);
+            for threshold in [0, 3, 4, 5, 9] {
+                let rules = Rules {
+                    threshold,
+                    ..Rules::default()
+                };
+                let expected = peel(&matrix, &rules);
+                let packed = peel_packed(&BitGrid::from_rows(&matrix), threshold);
+                assert_eq!(expected.wave_counts, packed.wave_counts);
+                assert_eq!(expected.stable, packed.stable.to_rows());
+            }
+        }
+
+        let text = "..@@.@@@@.\n@@@.@.@.@@\n@@@@@.@.@@\n";
+        let packed = parse_packed(text.as_bytes()).unwrap();
+        assert_eq!(parse_grid(text.as_bytes()).unwrap(), packed.to_rows());
+        assert!(parse_packed("@@\n@\n".as_bytes()).is_err());
+        assert_eq!(0, peel_packed(&BitGrid::new(0, 0), 4).total);
+    }
+
+    #[test]
+    fn test_parse_grid() {
+        let grid = parse_grid("@.@\r\n\n.@.\n".as_bytes()).unwrap();
+        assert_eq!(vec![vec![1, 0, 1], vec![0, 1, 0]], grid);
+        assert!(parse_grid("@@\n@\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use anyhow::{anyhow, bail, ensure, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// `(x, y)`: column then row, with `y` growing downwards as in the puzzle text.
+pub type Point = (i32, i32);
+
+/// A set of cells translated so its bounding box starts at `(0, 0)`, kept sorted so that equal
+/// shapes compare equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Shape {
+    cells: Vec<Point>,
+}
+
+/// One of the eight symmetries of the square: `rotations` quarter turns, optionally after a
+/// horizontal flip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Transform {
+    pub flipped: bool,
+    pub rotations: u8,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::new(false, 0),
+        Transform::new(false, 1),
+        Transform::new(false, 2),
+        Transform::new(false, 3),
+        Transform::new(true, 0),
+        Transform::new(true, 1),
+        Transform::new(true, 2),
+        Transform::new(true, 3),
+    ];
+
+    const fn new(flipped: bool, rotations: u8) -> Self {
+        Self { flipped, rotations }
+    }
+
+    fn apply(&self, (mut x, mut y): Point) -> Point {
+        if self.flipped {
+            x = -x;
+        }
+        for _ in 0..self.rotations {
+            (x, y) = (-y, x);
+        }
+        (x, y)
+    }
+}
+
+impl Shape {
+    pub fn new(cells: impl IntoIterator<Item = Point>) -> Self {
+        let cells: BTreeSet<Point> = cells.into_iter().collect();
+        let min_x = cells.iter().map(|(x, _)| *x).min().unwrap_or(0);
+        let min_y = cells.iter().map(|(_, y)| *y).min().unwrap_or(0);
+        let mut cells: Vec<Point> = cells
+            .into_iter()
+            .map(|(x, y)| (x - min_x, y - min_y))
+            .collect();
+        cells.sort();
+        Self { cells }
+    }
+
+    pub fn cells(&self) -> &[Point] {
+        &self.cells
+    }
+
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Bounding box as `(width, height)`.
+    pub fn dims(&self) -> (i32, i32) {
+        if self.cells.is_empty() {
+            return (0, 0);
+        }
+        let max_x = self.cells.iter().map(|(x, _)| *x).max().unwrap();
+        let max_y = self.cells.iter().map(|(_, y)| *y).max().unwrap();
+        (max_x + 1, max_y + 1)
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.cells.binary_search(&p).is_ok()
+    }
+
+    pub fn transform(&self, t: Transform) -> Shape {
+        Shape::new(self.cells.iter().map(|&p| t.apply(p)))
+    }
+
+    pub fn rotate90(&self) -> Shape {
+        self.transform(Transform::new(false, 1))
+    }
+
+    pub fn flip_h(&self) -> Shape {
+        self.transform(Transform::new(true, 0))
+    }
+
+    /// The shape under each of the eight symmetries, in `Transform::ALL` order.
+    pub fn transforms(&self) -> [Shape; 8] {
+        Transform::ALL.map(|t| self.transform(t))
+    }
+
+    /// The distinct orientations, sorted.
+    pub fn orientations(&self) -> Vec<Shape> {
+        let distinct: BTreeSet<Shape> = self.transforms().into_iter().collect();
+        distinct.into_iter().collect()
+    }
+
+    /// The smallest orientation, so congruent shapes share one representative.
+    pub fn canonical(&self) -> Shape {
+        self.transforms().into_iter().min().unwrap()
+    }
+
+    /// The symmetries that map the shape onto itself.
+    pub fn symmetry_group(&self) -> Vec<Transform> {
+        Transform::ALL
+            .into_iter()
+            .filter(|&t| self.transform(t) == *self)
+            .collect()
+    }
+
+    /// Row-major bits of the bounding box (`y * width + x`), or `None` if it exceeds 64 cells.
+    pub fn bitmask(&self) -> Option<u64> {
+        let (w, h) = self.dims();
+        if w * h > 64 {
+            return None;
+        }
+        Some(
+            self.cells
+                .iter()
+                .fold(0, |acc, &(x, y)| acc | 1 << (y * w + x)),
+        )
+    }
+
+    /// One bitmask per row, bit `x` set for each filled column.
+    pub fn row_masks(&self) -> Vec<u64> {
+        let (_, h) = self.dims();
+        let mut rows = vec![0; h as usize];
+        for &(x, y) in &self.cells {
+            rows[y as usize] |= 1 << x;
+        }
+        rows
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (w, h) = self.dims();
+        for y in 0..h {
+            for x in 0..w {
+                write!(f, "{}", if self.contains((x, y)) { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// A `WxH: c0 c1 ...` line: the area to fill and how many of each shape id to place in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub width: usize,
+    pub height: usize,
+    pub counts: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Puzzle {
+    pub shapes: HashMap<usize, Shape>,
+    pub regions: Vec<Region>,
+}
+
+impl Puzzle {
+    /// Parses `id:` headers followed by `#`/`.` rows, and `WxH: counts...` region lines.
+    pub fn parse(input: &str) -> Result<Self> {
+        let lines: Vec<&str> = input.lines().collect();
+        let mut puzzle = Puzzle::default();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i].trim();
+            i += 1;
+            if line.is_empty() {
+                continue;
+            }
+
+            let Some((head, rest)) = line.split_once(':') else {
+                bail!("line {i}: expected a shape header or a region, got {line:?}");
+            };
+            if let Some((w, h)) = head.trim().split_once('x') {
+                let counts = rest
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()?;
+                puzzle.regions.push(Region {
+                    width: w.parse()?,
+                    height: h.parse()?,
+                    counts,
+                });
+                continue;
+            }
+
+            let shape_id: usize = head.parse()?;
+            ensure!(
+                rest.trim().is_empty(),
+                "line {i}: unexpected text after shape header"
+            );
+            let mut cells = Vec::new();
+            let mut y = 0;
+            while i < lines.len() {
+                let row = lines[i];
+                let t = row.trim();
+                // A blank line or the next header (digit:) ends the shape.
+                if t.is_empty() || t.contains(':') {
+                    break;
+                }
+                for (x, ch) in row.chars().enumerate() {
+                    match ch {
+                        '#' => cells.push((x as i32, y)),
+                        '.' | ' ' => {}
+                        _ => bail!("line {}: unexpected {ch:?} in shape {shape_id}", i + 1),
+                    }
+                }
+                y += 1;
+                i += 1;
+            }
+            if puzzle.shapes.insert(shape_id, Shape::new(cells)).is_some() {
+                return Err(anyhow!("shape {shape_id} is defined twice"));
+            }
+        }
+
+        Ok(puzzle)
+    }
+
+    /// Every shape id mapped to its distinct orientations.
+    pub fn orientations(&self) -> HashMap<usize, Vec<Shape>> {
+        self.shapes
+            .iter()
+            .map(|(&id, shape)| (id, shape.orientations()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(rows: &str) -> Shape {
+        let cells = rows.lines().enumerate().flat_map(|(y, row)| {
+            row.chars()
+                .enumerate()
+                .filter(|&(_, c)| c == '#')
+                .map(move |(x, _)| (x as i32, y as i32))
+        });
+        Shape::new(cells)
+    }
+
+    #[test]
+    fn test_orientations() {
+        let l = shape("#.\n#.\n##");
+        assert_eq!(8, l.orientations().len());
+        assert_eq!(1, l.symmetry_group().len());
+        assert_eq!(l.canonical(), l.rotate90().flip_h().canonical());
+
+        let square = shape("##\n##");
+        assert_eq!(1, square.orientations().len());
+        assert_eq!(8, square.symmetry_group().len());
+
+        let plus = shape(".#.\n###\n.#.");
+        assert_eq!((3, 3), plus.dims());
+        assert_eq!(5, plus.area());
+        assert_eq!(Some(0b010_111_010), plus.bitmask());
+        assert_eq!(vec![0b010, 0b111, 0b010], plus.row_masks());
+    }
+
+    #[test]
+    fn test_parse() {
+        let puzzle = Puzzle::parse("0:\n###\n#..\n###\n\n1:\n##\n\n4x4: 0 2\n12x5: 1 0\n").unwrap();
+        assert_eq!(2, puzzle.shapes.len());
+        assert_eq!(7, puzzle.shapes[&0].area());
+        assert_eq!("###\n#..\n###\n", puzzle.shapes[&0].to_string());
+        assert_eq!(
+            Region {
+                width: 12,
+                height: 5,
+                counts: vec![1, 0]
+            },
+            puzzle.regions[1]
+        );
+        assert!(Puzzle::parse("0:\n#x#\n").is_err());
+    }
+}
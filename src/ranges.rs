@@ -0,0 +1,369 @@
+use anyhow::{anyhow, bail, ensure, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::BufRead;
+
+/// `start..=end`, never empty. Every `u128` range is representable, including `0..=u128::MAX`
+/// whose length does not fit in a `u128`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Inclusive {
+    start: u128,
+    end: u128,
+}
+
+impl Inclusive {
+    pub fn new(start: u128, end: u128) -> Result<Self> {
+        ensure!(start <= end, "inverted range {start}-{end}");
+        Ok(Self { start, end })
+    }
+
+    pub fn start(&self) -> u128 {
+        self.start
+    }
+
+    pub fn end(&self) -> u128 {
+        self.end
+    }
+
+    pub fn contains(&self, value: u128) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    /// The number of values, `None` only for `0..=u128::MAX`.
+    pub fn count(&self) -> Option<u128> {
+        (self.end - self.start).checked_add(1)
+    }
+
+    /// The same values as `start..end + 1`, `None` if `end` is `u128::MAX`.
+    pub fn to_half_open(&self) -> Option<HalfOpen> {
+        Some(HalfOpen {
+            start: self.start,
+            end: self.end.checked_add(1)?,
+        })
+    }
+
+    /// Whether the two can be written as one range: they overlap or `other` starts right after
+    /// `self` ends (or the reverse).
+    pub fn touches(&self, other: &Inclusive) -> bool {
+        let (first, second) = if self.start <= other.start {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        first
+            .end
+            .checked_add(1)
+            .is_none_or(|next| second.start <= next)
+    }
+
+    /// The smallest range covering both; only a union if they [`touch`](Self::touches).
+    pub fn hull(&self, other: &Inclusive) -> Inclusive {
+        Inclusive {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+}
+
+impl fmt::Display for Inclusive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// `start..end`, possibly empty. Cannot include `u128::MAX`; use [`Inclusive`] for that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HalfOpen {
+    start: u128,
+    end: u128,
+}
+
+impl HalfOpen {
+    pub fn new(start: u128, end: u128) -> Result<Self> {
+        ensure!(start <= end, "inverted range {start}..{end}");
+        Ok(Self { start, end })
+    }
+
+    pub fn start(&self) -> u128 {
+        self.start
+    }
+
+    pub fn end(&self) -> u128 {
+        self.end
+    }
+
+    pub fn contains(&self, value: u128) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn len(&self) -> u128 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The same values as `start..=end - 1`, `None` if empty.
+    pub fn to_inclusive(&self) -> Option<Inclusive> {
+        (!self.is_empty()).then(|| Inclusive {
+            start: self.start,
+            end: self.end - 1,
+        })
+    }
+}
+
+impl fmt::Display for HalfOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+
+/// One `a-b` entry of a range list, with the byte offset where it starts in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeToken {
+    pub start: u128,
+    pub end: u128,
+    pub offset: usize,
+}
+
+impl RangeToken {
+    pub fn inclusive(&self) -> Inclusive {
+        // The reader rejects inverted ranges.
+        Inclusive {
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+/// Streams `a-b` ranges separated by commas and/or newlines, e.g. `1-3,5-7,\n9-11`.
+///
+/// Spaces, tabs and `\r` around entries and around the `-` are ignored, and a comma may end a
+/// line or the input. Errors name the byte offset of the offending entry, and the iterator stops
+/// after the first one. Overlapping ranges are rejected unless [`RangeReader::allow_overlaps`]
+/// is set.
+pub struct RangeReader<R> {
+    reader: R,
+    radix: u32,
+    allow_overlaps: bool,
+    stop_at_blank_line: bool,
+    offset: usize,
+    line_has_entry: bool,
+    seen: BTreeMap<u128, u128>,
+    done: bool,
+}
+
+impl<R: BufRead> RangeReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            radix: 10,
+            allow_overlaps: false,
+            stop_at_blank_line: false,
+            offset: 0,
+            line_has_entry: false,
+            seen: BTreeMap::new(),
+            done: false,
+        }
+    }
+
+    /// Bounds written in `radix` (2..=36) instead of decimal.
+    pub fn radix(mut self, radix: u32) -> Self {
+        self.radix = radix;
+        self
+    }
+
+    pub fn allow_overlaps(mut self) -> Self {
+        self.allow_overlaps = true;
+        self
+    }
+
+    /// End the list at the first blank line, leaving the reader just after it, as in day 05
+    /// where the ranges are followed by a blank line and a second section.
+    pub fn stop_at_blank_line(mut self) -> Self {
+        self.stop_at_blank_line = true;
+        self
+    }
+
+    /// The underlying reader, positioned after the last byte consumed.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads up to and including the next `,` or `\n`, returning the field and its terminator
+    /// (`None` at end of input).
+    fn read_field(&mut self) -> Result<(Vec<u8>, Option<u8>)> {
+        let mut field = Vec::new();
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                return Ok((field, None));
+            }
+            match buf.iter().position(|&b| b == b',' || b == b'\n') {
+                Some(i) => {
+                    let sep = buf[i];
+                    field.extend_from_slice(&buf[..i]);
+                    self.reader.consume(i + 1);
+                    self.offset += i + 1;
+                    return Ok((field, Some(sep)));
+                }
+                None => {
+                    let n = buf.len();
+                    field.extend_from_slice(buf);
+                    self.reader.consume(n);
+                    self.offset += n;
+                }
+            }
+        }
+    }
+
+    fn next_token(&mut self) -> Result<Option<RangeToken>> {
+        if !(2..=36).contains(&self.radix) {
+            bail!("radix {} out of range", self.radix);
+        }
+        loop {
+            let field_offset = self.offset;
+            let (field, sep) = self.read_field()?;
+            let text = std::str::from_utf8(&field)
+                .map_err(|_| anyhow!("byte {field_offset}: range is not valid UTF-8"))?;
+            let leading = text.len() - text.trim_start().len();
+            let text = text.trim();
+            let offset = field_offset + leading;
+
+            if text.is_empty() {
+                match sep {
+                    None => return Ok(None),
+                    Some(b',') => bail!("byte {offset}: empty range before ','"),
+                    _ if !self.line_has_entry && self.stop_at_blank_line => return Ok(None),
+                    _ => {
+                        self.line_has_entry = false;
+                        continue;
+                    }
+                }
+            }
+            self.line_has_entry = sep != Some(b'\n');
+
+            let (a, b) = text
+                .split_once('-')
+                .ok_or_else(|| anyhow!("byte {offset}: expected start-end, got {text:?}"))?;
+            let parse = |s: &str| {
+                u128::from_str_radix(s.trim(), self.radix)
+                    .map_err(|e| anyhow!("byte {offset}: bad bound {:?}: {e}", s.trim()))
+            };
+            let (start, end) = (parse(a)?, parse(b)?);
+            if start > end {
+                bail!("byte {offset}: reversed range {text}");
+            }
+            if !self.allow_overlaps {
+                if let Some((&s, &e)) = self.seen.range(..=end).next_back() {
+                    if e >= start {
+                        bail!("byte {offset}: range {text} overlaps {s}-{e}");
+                    }
+                }
+                self.seen.insert(start, end);
+            }
+            return Ok(Some(RangeToken { start, end, offset }));
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for RangeReader<R> {
+    type Item = Result<RangeToken>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let token = self.next_token().transpose();
+        if !matches!(token, Some(Result::Ok(_))) {
+            self.done = true;
+        }
+        token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Read};
+
+    fn ranges(input: &str) -> Result<Vec<(u128, u128)>> {
+        RangeReader::new(input.as_bytes())
+            .map(|t| t.map(|t| (t.start, t.end)))
+            .collect()
+    }
+
+    #[test]
+    fn test_separators() {
+        let expected = vec![(1, 3), (5, 7), (9, 11)];
+        assert_eq!(expected, ranges("1-3,5-7,\n9-11\n").unwrap());
+        assert_eq!(expected, ranges("1-3, 5 - 7,\r\n9-11,").unwrap());
+        assert_eq!(expected, ranges("1-3\r\n5-7\r\n\r\n9-11").unwrap());
+        assert!(ranges("").unwrap().is_empty());
+
+        // A one-byte buffer forces fields to span several reads.
+        let tokens: Vec<_> =
+            RangeReader::new(BufReader::with_capacity(1, "10-20,\n 30-40".as_bytes()))
+                .collect::<Result<_>>()
+                .unwrap();
+        assert_eq!(8, tokens[1].offset);
+        assert_eq!((30, 40), (tokens[1].start, tokens[1].end));
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |input: &str| ranges(input).unwrap_err().to_string();
+        assert_eq!("byte 4: reversed range 5-3", err("1-2,5-3"));
+        assert_eq!("byte 5: range 2-4 overlaps 1-3", err("1-3,\n2-4"));
+        assert!(err("1-3,,4-5").starts_with("byte 4: empty range"));
+        assert!(err("1-3,x").starts_with("byte 4: expected start-end"));
+        assert!(err("1-3,4-1a").starts_with("byte 4: bad bound"));
+        assert_eq!(
+            vec![(1, 3), (2, 4)],
+            RangeReader::new("1-3\n2-4".as_bytes())
+                .allow_overlaps()
+                .map(|t| t.map(|t| (t.start, t.end)))
+                .collect::<Result<Vec<_>>>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_range_types() {
+        assert!(Inclusive::new(5, 3).is_err());
+        assert!(HalfOpen::new(5, 3).is_err());
+
+        let full = Inclusive::new(0, u128::MAX).unwrap();
+        assert_eq!(None, full.count());
+        assert_eq!(None, full.to_half_open());
+        assert!(full.contains(u128::MAX));
+
+        let top = Inclusive::new(u128::MAX - 1, u128::MAX).unwrap();
+        assert_eq!(Some(2), top.count());
+        assert!(top.touches(&Inclusive::new(10, u128::MAX - 2).unwrap()));
+        assert!(!top.touches(&Inclusive::new(10, u128::MAX - 3).unwrap()));
+        assert!(top.touches(&full));
+        assert_eq!(full, top.hull(&full));
+
+        let half = Inclusive::new(3, 5).unwrap().to_half_open().unwrap();
+        assert_eq!((3, 6, 3), (half.start(), half.end(), half.len()));
+        assert!(half.contains(5) && !half.contains(6));
+        assert_eq!("3..6", half.to_string());
+        assert_eq!("3-5", half.to_inclusive().unwrap().to_string());
+        let empty = HalfOpen::new(7, 7).unwrap();
+        assert!(empty.is_empty() && empty.to_inclusive().is_none());
+    }
+
+    #[test]
+    fn test_blank_line() {
+        let mut reader = RangeReader::new("3-5\n10-14\n\n1\n5\n".as_bytes()).stop_at_blank_line();
+        assert_eq!(2, reader.by_ref().count());
+        let mut rest = String::new();
+        reader.into_inner().read_to_string(&mut rest).unwrap();
+        assert_eq!("1\n5\n", rest);
+
+        let hex: Vec<_> = RangeReader::new("a-F,10-1f".as_bytes()).radix(16).collect();
+        assert_eq!(31, hex[1].as_ref().unwrap().end);
+    }
+}
@@ -0,0 +1,235 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// An exact fraction `numer / denom`, always kept in lowest terms with a positive denominator.
+///
+/// Arithmetic panics on `i128` overflow instead of silently losing precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numer: i128,
+    denom: i128,
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    a = a.abs();
+    b = b.abs();
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { numer: 0, denom: 1 };
+    pub const ONE: Rational = Rational { numer: 1, denom: 1 };
+
+    pub fn new(numer: i128, denom: i128) -> Self {
+        assert!(denom != 0, "zero denominator");
+        let g = gcd(numer, denom);
+        let sign = if denom < 0 { -1 } else { 1 };
+        Self {
+            numer: sign * numer / g,
+            denom: sign * denom / g,
+        }
+    }
+
+    pub fn from_integer(n: i128) -> Self {
+        Self { numer: n, denom: 1 }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.numer
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.denom
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numer == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denom == 1
+    }
+
+    /// The value as an integer, or `None` if it has a fractional part.
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.numer)
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            numer: self.numer.abs(),
+            denom: self.denom,
+        }
+    }
+
+    pub fn recip(&self) -> Self {
+        Self::new(self.denom, self.numer)
+    }
+
+    /// Largest integer not greater than the value.
+    pub fn floor(&self) -> i128 {
+        self.numer.div_euclid(self.denom)
+    }
+
+    /// Smallest integer not less than the value.
+    pub fn ceil(&self) -> i128 {
+        -(-self.numer).div_euclid(self.denom)
+    }
+
+    /// The nearest `f64`, for estimates that do not need to be exact.
+    pub fn to_f64(&self) -> f64 {
+        self.numer as f64 / self.denom as f64
+    }
+}
+
+fn overflow() -> ! {
+    panic!("rational arithmetic overflowed i128")
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Rational) -> Rational {
+        // Scale by lcm(denoms) rather than their product to keep intermediates small.
+        let g = gcd(self.denom, rhs.denom);
+        let l = self.denom / g;
+        let r = rhs.denom / g;
+        let numer = self
+            .numer
+            .checked_mul(r)
+            .zip(rhs.numer.checked_mul(l))
+            .and_then(|(a, b)| a.checked_add(b))
+            .unwrap_or_else(|| overflow());
+        let denom = l.checked_mul(rhs.denom).unwrap_or_else(|| overflow());
+        Rational::new(numer, denom)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Rational) -> Rational {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Rational) -> Rational {
+        // Cross-cancel first so the products only overflow when the result would.
+        let g1 = gcd(self.numer, rhs.denom);
+        let g2 = gcd(rhs.numer, self.denom);
+        let numer = (self.numer / g1)
+            .checked_mul(rhs.numer / g2)
+            .unwrap_or_else(|| overflow());
+        let denom = (self.denom / g2)
+            .checked_mul(rhs.denom / g1)
+            .unwrap_or_else(|| overflow());
+        Rational::new(numer, denom)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Rational) -> Rational {
+        self * rhs.recip()
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            numer: -self.numer,
+            denom: self.denom,
+        }
+    }
+}
+
+impl AddAssign for Rational {
+    fn add_assign(&mut self, rhs: Rational) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Rational {
+    fn sub_assign(&mut self, rhs: Rational) {
+        *self = *self - rhs;
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (*self - *other).numer.cmp(&0)
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Self::from_integer(n)
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Self::from_integer(n as i128)
+    }
+}
+
+impl From<usize> for Rational {
+    fn from(n: usize) -> Self {
+        Self::from_integer(n as i128)
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denom == 1 {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalized() {
+        let r = Rational::new(6, -4);
+        assert_eq!((-3, 2), (r.numer(), r.denom()));
+        assert_eq!(Rational::ZERO, Rational::new(0, -7));
+        assert_eq!("-3/2", r.to_string());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let third = Rational::new(1, 3);
+        let sixth = Rational::new(1, 6);
+        assert_eq!(Rational::new(1, 2), third + sixth);
+        assert_eq!(Rational::new(1, 6), third - sixth);
+        assert_eq!(Rational::new(1, 18), third * sixth);
+        assert_eq!(Rational::from(2i64), third / sixth);
+        assert!(sixth < third);
+        assert_eq!(
+            (-2, -1),
+            (Rational::new(-3, 2).floor(), Rational::new(-3, 2).ceil())
+        );
+    }
+}
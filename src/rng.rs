@@ -0,0 +1,51 @@
+/// A seeded pseudo-random generator (SplitMix64) for generated grids and randomised tests: the
+/// same seed always gives the same sequence, on every platform.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..bound`. The modulo bias is below `bound / 2^64`.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "empty range");
+        self.next_u64() % bound
+    }
+
+    /// `true` with probability `numer / denom`.
+    pub fn chance(&mut self, numer: u64, denom: u64) -> bool {
+        self.below(denom) < numer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        // The first outputs of SplitMix64 seeded with 0.
+        let mut rng = Rng::new(0);
+        assert_eq!(0xe220_a839_7b1d_cdaf, rng.next_u64());
+        assert_eq!(0x6e78_9e6a_a1b9_65f4, rng.next_u64());
+
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        let draws: Vec<u64> = (0..1_000).map(|_| a.below(10)).collect();
+        assert_eq!(draws, (0..1_000).map(|_| b.below(10)).collect::<Vec<_>>());
+        assert!(draws.iter().all(|&d| d < 10));
+        assert!((0..10).all(|d| draws.contains(&d)));
+    }
+}
@@ -0,0 +1,6 @@
+/// Clears the screen and moves the cursor to the top-left corner, so each frame of a
+/// visualizer draws over the last.
+pub const CLEAR: &str = "\x1b[2J\x1b[1;1H";
+
+/// Back to the terminal's default colours.
+pub const RESET: &str = "\x1b[0m";